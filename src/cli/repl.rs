@@ -9,8 +9,8 @@ use std::path::PathBuf;
 use term_size;
 
 use semantics::{self, DeclarationEnv, DefinitionEnv, TcEnv};
-use syntax::parse;
 use syntax::translation::DesugarEnv;
+use syntax::{core, parse, raw};
 
 /// Options for the `repl` subcommand
 #[derive(Debug, StructOpt)]
@@ -35,6 +35,10 @@ pub struct Opts {
     )]
     pub history_file: PathBuf,
 
+    /// Limit the number of reduction steps taken when evaluating a term
+    #[structopt(long = "eval-fuel")]
+    pub eval_fuel: Option<u64>,
+
     /// Files to preload into the REPL
     #[structopt(name = "FILE", parse(from_os_str))]
     pub files: Vec<PathBuf>,
@@ -89,6 +93,7 @@ pub fn run(color: ColorChoice, opts: &Opts) -> Result<(), Error> {
     let writer = StandardStream::stderr(color);
     let mut desugar_env = DesugarEnv::new();
     let mut tc_env = TcEnv::default();
+    tc_env.set_eval_fuel(opts.eval_fuel);
 
     interface.set_prompt(&opts.prompt)?;
     interface.set_report_signal(Signal::Interrupt, true);
@@ -171,7 +176,7 @@ fn eval_print(
 
         ReplCommand::Eval(parse_term) => {
             let raw_term = parse_term.desugar(desugar_env);
-            let (term, inferred) = semantics::infer_term(tc_env, &raw_term)?;
+            let (term, inferred) = infer_term(tc_env, &raw_term)?;
             let evaluated = semantics::nf_term(tc_env, &term)?;
 
             let ann_term = Term::Ann(Box::new(evaluated.resugar()), Box::new(inferred.resugar()));
@@ -182,7 +187,7 @@ fn eval_print(
             use syntax::core::{RcTerm, Term};
 
             let raw_term = parse_term.desugar(desugar_env);
-            let (term, inferred) = semantics::infer_term(tc_env, &raw_term)?;
+            let (term, inferred) = infer_term(tc_env, &raw_term)?;
 
             let ann_term = Term::Ann(term, RcTerm::from(Term::from(&*inferred)));
 
@@ -195,7 +200,7 @@ fn eval_print(
         },
         ReplCommand::Let(name, parse_term) => {
             let raw_term = parse_term.desugar(desugar_env);
-            let (term, inferred) = semantics::infer_term(tc_env, &raw_term)?;
+            let (term, inferred) = infer_term(tc_env, &raw_term)?;
 
             let ann_term = Term::Ann(
                 Box::new(Term::Name(ByteIndex::default(), name.clone())),
@@ -212,9 +217,9 @@ fn eval_print(
         },
         ReplCommand::TypeOf(parse_term) => {
             let raw_term = parse_term.desugar(desugar_env);
-            let (_, inferred) = semantics::infer_term(tc_env, &raw_term)?;
+            let (_, inferred) = infer_term(tc_env, &raw_term)?;
 
-            println!("{}", inferred.resugar().to_doc().pretty(term_width()));
+            println!("{}", inferred.resugar().to_doc().group().pretty(term_width()));
         },
 
         ReplCommand::NoOp | ReplCommand::Error(_) => {},
@@ -224,6 +229,19 @@ fn eval_print(
     Ok(ControlFlow::Continue)
 }
 
+/// Infer the type of a term, filling in any metavariables that were solved
+/// along the way
+fn infer_term(
+    tc_env: &TcEnv,
+    raw_term: &raw::RcTerm,
+) -> Result<(core::RcTerm, core::RcType), semantics::TypeError> {
+    let (term, inferred) = semantics::infer_term(tc_env, raw_term)?;
+    Ok((
+        semantics::zonk_term(tc_env, &term)?,
+        semantics::zonk_value(tc_env, &inferred)?,
+    ))
+}
+
 #[derive(Clone)]
 enum ControlFlow {
     Break,
@@ -256,8 +274,8 @@ impl From<semantics::TypeError> for EvalPrintError {
     }
 }
 
-impl From<semantics::InternalError> for EvalPrintError {
-    fn from(src: semantics::InternalError) -> EvalPrintError {
+impl From<semantics::NormalizeError> for EvalPrintError {
+    fn from(src: semantics::NormalizeError) -> EvalPrintError {
         EvalPrintError::Type(src.into())
     }
 }
@@ -172,6 +172,11 @@ pub fn load_file(file: &FileMap) -> Result<core::Module, Vec<Diagnostic>> {
         .map(|err| err.to_diagnostic())
         .collect::<Vec<_>>();
 
+    // The desugarer can recover from parse errors, but the type checker can't
+    if !diagnostics.is_empty() {
+        return Err(diagnostics);
+    }
+
     let raw_module = concrete_module.desugar(&DesugarEnv::new());
     semantics::check_module(&TcEnv::default(), &raw_module).map_err(|err| {
         diagnostics.push(err.to_diagnostic());
@@ -0,0 +1,72 @@
+//! Checking the patterns of case expressions
+
+use codespan::{ByteIndex, ByteSpan};
+
+use syntax::concrete;
+use syntax::core::{Literal, Pattern, RcPattern};
+
+use semantics::errors::TypeError;
+
+/// Check that the patterns of a case expression cover every value of the
+/// head, and that none of the clauses are unreachable
+///
+/// We only have binder and literal patterns for now, so the patterns are
+/// exhaustive once we see a binder, or once both `true` and `false` have
+/// been seen. Any clause after that point is unreachable, as is a literal
+/// that has already been matched by an earlier clause.
+pub fn check_coverage(span: ByteSpan, patterns: &[(ByteSpan, RcPattern)]) -> Result<(), TypeError> {
+    let mut literals = Vec::new();
+    let mut is_exhaustive = false;
+
+    for &(pattern_span, ref pattern) in patterns {
+        if is_exhaustive {
+            return Err(TypeError::UnreachableClause { span: pattern_span });
+        }
+
+        match *strip_ann(pattern) {
+            Pattern::Binder(_) => is_exhaustive = true,
+            Pattern::Literal(ref literal) => {
+                if literals.contains(&literal) {
+                    return Err(TypeError::UnreachableClause { span: pattern_span });
+                }
+
+                literals.push(literal);
+                is_exhaustive = literals.contains(&&Literal::Bool(true))
+                    && literals.contains(&&Literal::Bool(false));
+            },
+            Pattern::Ann(_, _) => unreachable!(),
+        }
+    }
+
+    if is_exhaustive {
+        Ok(())
+    } else {
+        Err(TypeError::NonExhaustiveMatch {
+            span,
+            missing: Box::new(missing_pattern(&literals)),
+        })
+    }
+}
+
+/// Remove any type annotations from the outside of a pattern
+fn strip_ann(pattern: &RcPattern) -> &Pattern {
+    match *pattern.inner {
+        Pattern::Ann(ref pattern, _) => strip_ann(pattern),
+        ref pattern => pattern,
+    }
+}
+
+/// Find an example of a pattern that was not covered
+fn missing_pattern(literals: &[&Literal]) -> concrete::Pattern {
+    // FIXME: Draw these names from some environment?
+    let name = match (
+        literals.contains(&&Literal::Bool(true)),
+        literals.contains(&&Literal::Bool(false)),
+    ) {
+        (true, false) => "false",
+        (false, true) => "true",
+        (_, _) => "_",
+    };
+
+    concrete::Pattern::Binder(ByteIndex::default(), String::from(name))
+}
@@ -1,6 +1,9 @@
+use codespan::ByteSpan;
 use im::HashMap;
 use moniker::FreeVar;
+use std::cell::RefCell;
 use std::fmt;
+use std::rc::Rc;
 
 use syntax::core::{Literal, RcTerm, RcType, RcValue, Spine, Value};
 
@@ -261,45 +264,6 @@ fn default_extern_definitions() -> HashMap<&'static str, Extern> {
     }
 }
 
-fn default_global_declarations() -> HashMap<&'static str, RcType> {
-    use moniker::{Binder, Embed, Scope};
-
-    let universe0 = RcValue::from(Value::universe(0));
-    let bool_ty = RcValue::from(Value::global("Bool"));
-
-    hashmap!{
-        "Bool" => universe0.clone(),
-        "true" => bool_ty.clone(),
-        "false" => bool_ty.clone(),
-        "String" => universe0.clone(),
-        "Char" => universe0.clone(),
-        "U8" => universe0.clone(),
-        "U16" => universe0.clone(),
-        "U32" => universe0.clone(),
-        "U64" => universe0.clone(),
-        "I8" => universe0.clone(),
-        "I16" => universe0.clone(),
-        "I32" => universe0.clone(),
-        "I64" => universe0.clone(),
-        "F32" => universe0.clone(),
-        "F64" => universe0.clone(),
-        "Array" => RcValue::from(Value::Pi(Scope::new(
-            (Binder(FreeVar::fresh_unnamed()), Embed(RcValue::from(Value::global("U64")))),
-            RcValue::from(Value::Pi(Scope::new(
-                (Binder(FreeVar::fresh_unnamed()), Embed(universe0.clone())),
-                universe0.clone(),
-            ))),
-        ))),
-    }
-}
-
-fn default_global_definitions() -> HashMap<&'static str, RcValue> {
-    hashmap!{
-        "true" => RcValue::from(Value::Literal(Literal::Bool(true))),
-        "false" => RcValue::from(Value::Literal(Literal::Bool(false))),
-    }
-}
-
 /// An environment that contains declarations
 pub trait DeclarationEnv: Clone {
     fn get_global_declaration(&self, name: &str) -> Option<&RcType>;
@@ -312,6 +276,9 @@ pub trait DeclarationEnv: Clone {
 
 /// An environment that contains definitions
 pub trait DefinitionEnv: Clone {
+    /// The maximum number of reduction steps to take when normalizing a term,
+    /// or `None` if evaluation should be unlimited
+    fn get_eval_fuel(&self) -> Option<u64>;
     fn get_extern_definition(&self, name: &str) -> Option<&Extern>;
     fn get_global_definition(&self, name: &str) -> Option<&RcValue>;
     fn get_definition(&self, free_var: &FreeVar<String>) -> Option<&RcTerm>;
@@ -321,6 +288,29 @@ pub trait DefinitionEnv: Clone {
         T: IntoIterator<Item = (FreeVar<String>, RcTerm)>;
 }
 
+/// An environment that contains metavariables
+///
+/// Unlike the other environments, metavariables are shared between all copies
+/// of the environment. This ensures that solutions found when checking the
+/// body of a scope are still available once we have left it.
+pub trait MetaEnv: Clone {
+    fn fresh_meta(&self, span: ByteSpan, ty: RcType) -> FreeVar<String>;
+    fn get_meta_declaration(&self, meta: &FreeVar<String>) -> Option<(ByteSpan, RcType)>;
+    fn get_meta_solution(&self, meta: &FreeVar<String>) -> Option<RcValue>;
+    fn insert_meta_solution(&self, meta: FreeVar<String>, solution: RcValue);
+}
+
+/// A metavariable that was created during elaboration
+#[derive(Clone, Debug)]
+struct Meta {
+    /// The span of the hole that the metavariable was created for
+    span: ByteSpan,
+    /// The type of the metavariable
+    ty: RcType,
+    /// The solution that was found during unification, if any
+    solution: Option<RcValue>,
+}
+
 /// The type checking environment
 ///
 /// A default environment with entries for built-in types is provided via the
@@ -334,25 +324,94 @@ pub struct TcEnv {
     /// External definitions
     extern_definitions: HashMap<&'static str, Extern>,
     /// Global declarations
-    global_declarations: HashMap<&'static str, RcType>,
+    global_declarations: HashMap<String, RcType>,
     /// Global definitions
-    global_definitions: HashMap<&'static str, RcValue>,
+    global_definitions: HashMap<String, RcValue>,
     /// The type annotations of the binders we have passed over
     declarations: HashMap<FreeVar<String>, RcType>,
     /// Any definitions we have passed over
     definitions: HashMap<FreeVar<String>, RcTerm>,
+    /// The metavariables that have been created during elaboration
+    metas: Rc<RefCell<HashMap<FreeVar<String>, Meta>>>,
+    /// The maximum number of reduction steps to take when normalizing a term
+    eval_fuel: Option<u64>,
 }
 
 impl Default for TcEnv {
     fn default() -> TcEnv {
+        use moniker::{Binder, Embed, Scope};
+
+        let universe0 = RcValue::from(Value::universe(0));
+        let bool_ty = RcValue::from(Value::global("Bool"));
+        let bool_lit = |value| Some(RcValue::from(Value::Literal(Literal::Bool(value))));
+        let array_ty = RcValue::from(Value::Pi(Scope::new(
+            (
+                Binder(FreeVar::fresh_unnamed()),
+                Embed(RcValue::from(Value::global("U64"))),
+            ),
+            RcValue::from(Value::Pi(Scope::new(
+                (Binder(FreeVar::fresh_unnamed()), Embed(universe0.clone())),
+                universe0.clone(),
+            ))),
+        )));
+
+        let mut tc_env = TcEnv::empty();
+        tc_env.extern_definitions = default_extern_definitions();
+
+        tc_env.insert_global("Bool", universe0.clone(), None);
+        tc_env.insert_global("true", bool_ty.clone(), bool_lit(true));
+        tc_env.insert_global("false", bool_ty.clone(), bool_lit(false));
+        tc_env.insert_global("String", universe0.clone(), None);
+        tc_env.insert_global("Char", universe0.clone(), None);
+        tc_env.insert_global("U8", universe0.clone(), None);
+        tc_env.insert_global("U16", universe0.clone(), None);
+        tc_env.insert_global("U32", universe0.clone(), None);
+        tc_env.insert_global("U64", universe0.clone(), None);
+        tc_env.insert_global("I8", universe0.clone(), None);
+        tc_env.insert_global("I16", universe0.clone(), None);
+        tc_env.insert_global("I32", universe0.clone(), None);
+        tc_env.insert_global("I64", universe0.clone(), None);
+        tc_env.insert_global("F32", universe0.clone(), None);
+        tc_env.insert_global("F64", universe0.clone(), None);
+        tc_env.insert_global("Array", array_ty, None);
+
+        tc_env
+    }
+}
+
+impl TcEnv {
+    /// Create an environment without any of the built-in entries
+    pub fn empty() -> TcEnv {
         TcEnv {
-            extern_definitions: default_extern_definitions(),
-            global_declarations: default_global_declarations(),
-            global_definitions: default_global_definitions(),
+            extern_definitions: hashmap!{},
+            global_declarations: hashmap!{},
+            global_definitions: hashmap!{},
             declarations: hashmap!{},
             definitions: hashmap!{},
+            metas: Rc::new(RefCell::new(hashmap!{})),
+            eval_fuel: None,
         }
     }
+
+    /// Add a global name with the given type, along with its value if it has
+    /// a definition
+    ///
+    /// Names without a definition are treated as opaque primitives, like
+    /// `String` or `U8`.
+    pub fn insert_global(&mut self, name: impl Into<String>, ty: RcType, value: Option<RcValue>) {
+        let name = name.into();
+        match value {
+            Some(value) => self.global_definitions.insert(name.clone(), value),
+            None => self.global_definitions.remove(&name),
+        };
+        self.global_declarations.insert(name, ty);
+    }
+
+    /// Limit the number of reduction steps that can be taken when normalizing
+    /// a term, or remove the limit with `None`
+    pub fn set_eval_fuel(&mut self, fuel: Option<u64>) {
+        self.eval_fuel = fuel;
+    }
 }
 
 impl DeclarationEnv for TcEnv {
@@ -377,6 +436,10 @@ impl DeclarationEnv for TcEnv {
 }
 
 impl DefinitionEnv for TcEnv {
+    fn get_eval_fuel(&self) -> Option<u64> {
+        self.eval_fuel
+    }
+
     fn get_extern_definition(&self, name: &str) -> Option<&Extern> {
         self.extern_definitions.get(name)
     }
@@ -400,3 +463,36 @@ impl DefinitionEnv for TcEnv {
         self.definitions.extend(iter)
     }
 }
+
+impl MetaEnv for TcEnv {
+    fn fresh_meta(&self, span: ByteSpan, ty: RcType) -> FreeVar<String> {
+        let meta = FreeVar::fresh_named("?");
+        let entry = Meta {
+            span,
+            ty,
+            solution: None,
+        };
+        self.metas.borrow_mut().insert(meta.clone(), entry);
+        meta
+    }
+
+    fn get_meta_declaration(&self, meta: &FreeVar<String>) -> Option<(ByteSpan, RcType)> {
+        self.metas
+            .borrow()
+            .get(meta)
+            .map(|entry| (entry.span, entry.ty.clone()))
+    }
+
+    fn get_meta_solution(&self, meta: &FreeVar<String>) -> Option<RcValue> {
+        self.metas
+            .borrow()
+            .get(meta)
+            .and_then(|entry| entry.solution.clone())
+    }
+
+    fn insert_meta_solution(&self, meta: FreeVar<String>, solution: RcValue) {
+        if let Some(entry) = self.metas.borrow_mut().get_mut(&meta) {
+            entry.solution = Some(solution);
+        }
+    }
+}
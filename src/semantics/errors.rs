@@ -24,6 +24,8 @@ pub enum InternalError {
     ProjectedOnNonExistentField { label: syntax::Label },
     #[fail(display = "No patterns matched the given expression.")]
     NoPatternsApplicable,
+    #[fail(display = "Attempted to type check a term that failed to parse.")]
+    UnexpectedParseError { span: ByteSpan },
 }
 
 impl InternalError {
@@ -48,36 +50,73 @@ impl InternalError {
             InternalError::NoPatternsApplicable => {
                 Diagnostic::new_bug("no patterns matched the given expression")
             },
+            InternalError::UnexpectedParseError { span } => {
+                Diagnostic::new_bug("attempted to type check a term that failed to parse")
+                    .with_label(Label::new_primary(span).with_message("parse error here"))
+            },
+        }
+    }
+}
+
+/// An error produced during normalization
+#[derive(Debug, Fail, Clone, PartialEq)]
+pub enum NormalizeError {
+    /// Evaluation took more steps than the environment allows, which might
+    /// mean that the term does not terminate
+    #[fail(display = "Evaluation ran out of fuel.")]
+    OutOfFuel,
+    #[fail(display = "Internal error - this is a bug! {}", _0)]
+    Internal(#[cause] InternalError),
+}
+
+impl NormalizeError {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        match *self {
+            NormalizeError::OutOfFuel => {
+                Diagnostic::new_error("evaluation ran out of fuel - the term might not terminate")
+            },
+            NormalizeError::Internal(ref err) => err.to_diagnostic(),
         }
     }
 }
 
+impl From<InternalError> for NormalizeError {
+    fn from(src: InternalError) -> NormalizeError {
+        NormalizeError::Internal(src)
+    }
+}
+
 /// An error produced during type checking
 #[derive(Debug, Fail, Clone, PartialEq)]
 pub enum TypeError {
     #[fail(
         display = "Name had more than one declaration associated with it: `{}`",
-        binder,
+        label,
     )]
     DuplicateDeclarations {
         original_span: ByteSpan,
         duplicate_span: ByteSpan,
-        binder: Binder<String>,
+        label: syntax::Label,
     },
-    #[fail(display = "Declaration followed definition: `{}`", binder)]
+    #[fail(display = "Declaration followed definition: `{}`", label)]
     DeclarationFollowedDefinition {
         definition_span: ByteSpan,
         declaration_span: ByteSpan,
-        binder: Binder<String>,
+        label: syntax::Label,
+    },
+    #[fail(display = "Declaration without definition: `{}`", label)]
+    DeclarationWithoutDefinition {
+        declaration_span: ByteSpan,
+        label: syntax::Label,
     },
     #[fail(
         display = "Name had more than one definition associated with it: `{}`",
-        binder,
+        label,
     )]
     DuplicateDefinitions {
         original_span: ByteSpan,
         duplicate_span: ByteSpan,
-        binder: Binder<String>,
+        label: syntax::Label,
     },
     #[fail(
         display = "Applied an argument to a non-function type `{}`",
@@ -121,6 +160,13 @@ pub enum TypeError {
     AmbiguousFloatLiteral { span: ByteSpan },
     #[fail(display = "Empty case expressions need type annotations.")]
     AmbiguousEmptyCase { span: ByteSpan },
+    #[fail(display = "Non-exhaustive patterns: `{}` not covered", missing)]
+    NonExhaustiveMatch {
+        span: ByteSpan,
+        missing: Box<concrete::Pattern>,
+    },
+    #[fail(display = "Unreachable case clause")]
+    UnreachableClause { span: ByteSpan },
     #[fail(
         display = "Unable to elaborate hole, expected: `{:?}`",
         expected,
@@ -192,6 +238,8 @@ pub enum TypeError {
         expected_label: syntax::Label,
         found: Box<concrete::Term>,
     },
+    #[fail(display = "Evaluation ran out of fuel.")]
+    OutOfFuel,
     #[fail(display = "Internal error - this is a bug! {}", _0)]
     Internal(#[cause] InternalError),
 }
@@ -200,14 +248,15 @@ impl TypeError {
     /// Convert the error into a diagnostic message
     pub fn to_diagnostic(&self) -> Diagnostic {
         match *self {
+            TypeError::OutOfFuel => NormalizeError::OutOfFuel.to_diagnostic(),
             TypeError::Internal(ref err) => err.to_diagnostic(),
             TypeError::DuplicateDeclarations {
                 original_span,
                 duplicate_span,
-                ref binder,
+                ref label,
             } => Diagnostic::new_error(format!(
                 "name had more than one declaration associated with it `{}`",
-                binder,
+                label,
             )).with_label(
                 Label::new_primary(duplicate_span).with_message("the duplicated declaration"),
             ).with_label(
@@ -216,19 +265,24 @@ impl TypeError {
             TypeError::DeclarationFollowedDefinition {
                 definition_span,
                 declaration_span,
-                binder: _,
+                label: _,
             } => Diagnostic::new_error(format!("declarations cannot follow definitions"))
                 .with_label(Label::new_primary(declaration_span).with_message("the declaration"))
                 .with_label(
                     Label::new_secondary(definition_span).with_message("the original definition"),
                 ),
+            TypeError::DeclarationWithoutDefinition {
+                declaration_span,
+                ref label,
+            } => Diagnostic::new_error(format!("declaration of `{}` has no definition", label))
+                .with_label(Label::new_primary(declaration_span).with_message("the declaration")),
             TypeError::DuplicateDefinitions {
                 original_span,
                 duplicate_span,
-                ref binder,
+                ref label,
             } => Diagnostic::new_error(format!(
                 "name had more than one definition associated with it `{}`",
-                binder,
+                label,
             )).with_label(
                 Label::new_primary(duplicate_span).with_message("the duplicated definition"),
             ).with_label(
@@ -285,6 +339,13 @@ impl TypeError {
             TypeError::AmbiguousEmptyCase { span } => Diagnostic::new_error(
                 "empty case expressions need type annotations",
             ).with_label(Label::new_primary(span).with_message("type annotation needed here")),
+            TypeError::NonExhaustiveMatch { span, ref missing } => Diagnostic::new_error(
+                format!("non-exhaustive patterns - `{}` not covered", missing),
+            ).with_label(Label::new_primary(span).with_message("the case expression")),
+            TypeError::UnreachableClause { span } => Diagnostic::new_error("unreachable clause")
+                .with_label(
+                    Label::new_primary(span).with_message("this pattern will never be matched"),
+                ),
             TypeError::UnableToElaborateHole {
                 span,
                 expected: None,
@@ -371,3 +432,12 @@ impl From<InternalError> for TypeError {
         TypeError::Internal(src)
     }
 }
+
+impl From<NormalizeError> for TypeError {
+    fn from(src: NormalizeError) -> TypeError {
+        match src {
+            NormalizeError::OutOfFuel => TypeError::OutOfFuel,
+            NormalizeError::Internal(err) => TypeError::Internal(err),
+        }
+    }
+}
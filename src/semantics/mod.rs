@@ -5,40 +5,140 @@
 //! For more information, check out the theory appendix of the Pikelet book.
 
 use codespan::ByteSpan;
-use moniker::{Binder, BoundTerm, Embed, FreeVar, Nest, Scope, Var};
+use moniker::{Binder, Embed, FreeVar, Nest, Scope, Var};
 
-use syntax::core::{
-    Item, Literal, Module, Pattern, RcPattern, RcTerm, RcType, RcValue, Term, Type, Value,
-};
+use syntax::core::{Item, Literal, Module, Pattern, RcPattern, RcTerm, RcType, RcValue, Term, Value};
 use syntax::raw;
 use syntax::translation::Resugar;
-use syntax::Level;
+use syntax::{Label, Level};
 
+mod coverage;
 mod env;
 mod errors;
 mod normalize;
 #[cfg(test)]
 mod tests;
+mod unify;
 
-pub use self::env::{DeclarationEnv, DefinitionEnv, Extern, TcEnv};
-pub use self::errors::{InternalError, TypeError};
+pub use self::env::{DeclarationEnv, DefinitionEnv, Extern, MetaEnv, TcEnv};
+pub use self::errors::{InternalError, NormalizeError, TypeError};
 pub use self::normalize::{match_value, nf_term};
+pub use self::unify::{zonk_term, zonk_value};
+
+use self::coverage::check_coverage;
+use self::unify::{force, unify};
+
+/// A declaration or definition that has been seen while checking the items of
+/// a module or let binding
+#[derive(Clone)]
+enum ForwardDecl {
+    /// A declaration that may be waiting to be defined
+    Pending(ByteSpan, RcTerm),
+    /// A definition, along with the span of its label
+    Defined(ByteSpan),
+}
+
+/// Keeps track of the declarations and definitions seen so far in a module or
+/// let binding, enforcing the rules about how they may be combined
+struct ForwardDecls {
+    /// Whether later definitions may shadow earlier ones
+    allow_shadowing: bool,
+    forward_declarations: im::HashMap<Label, ForwardDecl>,
+}
+
+impl ForwardDecls {
+    fn new(allow_shadowing: bool) -> ForwardDecls {
+        ForwardDecls {
+            allow_shadowing,
+            forward_declarations: im::HashMap::new(),
+        }
+    }
+
+    /// Ensure that a declaration for `label` can be added
+    fn check_declaration(&self, label_span: ByteSpan, label: &Label) -> Result<(), TypeError> {
+        match self.forward_declarations.get(label) {
+            // There's already a definition associated with this name -
+            // we can't add a new declaration for it!
+            Some(&ForwardDecl::Defined(definition_span)) => {
+                Err(TypeError::DeclarationFollowedDefinition {
+                    definition_span,
+                    declaration_span: label_span,
+                    label: label.clone(),
+                })
+            },
+            // There's a declaration  for this name already pending - we
+            // can't add a new one!
+            Some(&ForwardDecl::Pending(original_span, _)) => {
+                Err(TypeError::DuplicateDeclarations {
+                    original_span,
+                    duplicate_span: label_span,
+                    label: label.clone(),
+                })
+            },
+            // No previous declaration for this name was seen, so we can
+            // go-ahead and type check, elaborate, and then add it to
+            // the context
+            None => Ok(()),
+        }
+    }
+
+    /// Remember the declaration for when we get to a subsequent definition
+    fn insert_declaration(&mut self, label_span: ByteSpan, label: Label, ann: RcTerm) {
+        let declaration = ForwardDecl::Pending(label_span, ann);
+        self.forward_declarations.insert(label, declaration);
+    }
+
+    /// Record a definition of `label`, returning the pending declaration that
+    /// it should be checked against, if there is one
+    fn define(&mut self, label_span: ByteSpan, label: &Label) -> Result<Option<RcTerm>, TypeError> {
+        let ann = match self.forward_declarations.get(label).cloned() {
+            // This declaration was already given a definition, so this
+            // is an error!
+            //
+            // NOTE: Some languages (eg. Haskell, Agda, Idris, and
+            // Erlang) turn duplicate definitions into case matches.
+            // Languages like Elm don't. What should we do here?
+            Some(ForwardDecl::Defined(original_span)) if !self.allow_shadowing => {
+                return Err(TypeError::DuplicateDefinitions {
+                    original_span,
+                    duplicate_span: label_span,
+                    label: label.clone(),
+                });
+            },
+            // We found a prior declaration, so we'll use it as a basis
+            // for checking the definition
+            Some(ForwardDecl::Pending(_, ann)) => Some(ann),
+            // No prior declaration was found, so the type will have to be
+            // inferred from the given definition alone
+            Some(ForwardDecl::Defined(_)) | None => None,
+        };
+
+        // We must not remove this from the list of pending
+        // declarations, lest we encounter another declaration or
+        // definition of the same name later on!
+        let definition = ForwardDecl::Defined(label_span);
+        self.forward_declarations.insert(label.clone(), definition);
+
+        Ok(ann)
+    }
+
+    /// The earliest declaration that was never given a definition
+    fn first_pending(self) -> Option<(ByteSpan, Label)> {
+        self.forward_declarations
+            .into_iter()
+            .filter_map(|(label, forward_declaration)| match forward_declaration {
+                ForwardDecl::Pending(declaration_span, _) => Some((declaration_span, label)),
+                ForwardDecl::Defined(_) => None,
+            }).min_by_key(|&(declaration_span, _)| declaration_span.start())
+    }
+}
 
 /// Type check and elaborate a module
 pub fn check_module<Env>(env: &Env, raw_module: &raw::Module) -> Result<Module, TypeError>
 where
-    Env: DeclarationEnv + DefinitionEnv,
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
 {
-    use im::HashMap;
-
-    #[derive(Clone)]
-    pub enum ForwardDecl {
-        Pending(ByteSpan, RcTerm),
-        Defined(ByteSpan),
-    }
-
-    // Declarations that may be waiting to be defined
-    let mut forward_declarations = HashMap::new();
+    let mut forward_declarations = ForwardDecls::new(false);
     let mut env = env.clone();
     // The elaborated items, pre-allocated to improve performance
     let mut items = Vec::with_capacity(raw_module.items.len());
@@ -53,36 +153,10 @@ where
                 term: ref raw_term,
             } => {
                 // Ensure that this declaration has not already been seen
-                match forward_declarations.get(binder) {
-                    // There's already a definition associated with this name -
-                    // we can't add a new declaration for it!
-                    Some(&ForwardDecl::Defined(definition_span)) => {
-                        return Err(TypeError::DeclarationFollowedDefinition {
-                            definition_span,
-                            declaration_span: label_span,
-                            binder: binder.clone(),
-                        });
-                    },
-                    // There's a declaration  for this name already pending - we
-                    // can't add a new one!
-                    Some(&ForwardDecl::Pending(original_span, _)) => {
-                        return Err(TypeError::DuplicateDeclarations {
-                            original_span,
-                            duplicate_span: label_span,
-                            binder: binder.clone(),
-                        });
-                    },
-                    // No previous declaration for this name was seen, so we can
-                    // go-ahead and type check, elaborate, and then add it to
-                    // the context
-                    None => {},
-                }
-
+                forward_declarations.check_declaration(label_span, label)?;
                 // Ensure that the declaration's type annotation is actually a type
                 let (term, _) = infer_universe(&env, raw_term)?;
-                // Remember the declaration for when we get to a subsequent definition
-                let declaration = ForwardDecl::Pending(label_span, term.clone());
-                forward_declarations.insert(binder.clone(), declaration);
+                forward_declarations.insert_declaration(label_span, label.clone(), term.clone());
                 // Add the declaration to the elaborated items
                 items.push(Item::Declaration {
                     label: label.clone(),
@@ -97,35 +171,14 @@ where
                 ref binder,
                 term: ref raw_term,
             } => {
-                let (term, ty) = match forward_declarations.get(binder).cloned() {
-                    // This declaration was already given a definition, so this
-                    // is an error!
-                    //
-                    // NOTE: Some languages (eg. Haskell, Agda, Idris, and
-                    // Erlang) turn duplicate definitions into case matches.
-                    // Languages like Elm don't. What should we do here?
-                    Some(ForwardDecl::Defined(original_span)) => {
-                        return Err(TypeError::DuplicateDefinitions {
-                            original_span,
-                            duplicate_span: label_span,
-                            binder: binder.clone(),
-                        });
-                    },
-                    // We found a prior declaration, so we'll use it as a basis
-                    // for checking the definition
-                    Some(ForwardDecl::Pending(_, ty)) => {
-                        let ty = nf_term(&env, &ty)?;
+                let (term, ty) = match forward_declarations.define(label_span, label)? {
+                    Some(ann) => {
+                        let ty = nf_term(&env, &ann)?;
                         (check_term(&env, &raw_term, &ty)?, ty)
                     },
-                    // No prior declaration was found, so try to infer the type
-                    // from the given definition alone
                     None => infer_term(&env, &raw_term)?,
                 };
 
-                // We must not remove this from the list of pending
-                // declarations, lest we encounter another declaration or
-                // definition of the same name later on!
-                forward_declarations.insert(binder.clone(), ForwardDecl::Defined(label_span));
                 // Add the declaration and definition to the environment,
                 // allowing them to be used in later type checking
                 env.insert_declaration(binder.0.clone(), ty);
@@ -140,17 +193,114 @@ where
         }
     }
 
+    // Now that all of the items have been checked, fill in the metavariables
+    // that were solved along the way
+    let items = items
+        .into_iter()
+        .map(|item| match item {
+            Item::Declaration {
+                label,
+                binder,
+                term,
+            } => Ok(Item::Declaration {
+                label,
+                binder,
+                term: zonk_term(&env, &term)?,
+            }),
+            Item::Definition {
+                label,
+                binder,
+                term,
+            } => Ok(Item::Definition {
+                label,
+                binder,
+                term: zonk_term(&env, &term)?,
+            }),
+        }).collect::<Result<_, TypeError>>()?;
+
     Ok(Module { items })
 }
 
+/// The elaborated definitions of a let binding, in the order they were bound
+type LetDefinitions = Vec<(FreeVar<String>, RcTerm)>;
+
+/// Type check and elaborate the items of a let binding, returning the
+/// elaborated definitions along with an environment that they have been added
+/// to
+///
+/// Declarations follow the same rules as in modules, except that names may be
+/// shadowed by later definitions.
+fn check_let_items<Env>(
+    env: &Env,
+    raw_items: Vec<raw::LetItem>,
+) -> Result<(Env, LetDefinitions), TypeError>
+where
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
+{
+    let mut forward_declarations = ForwardDecls::new(true);
+    let mut env = env.clone();
+    let mut definitions = Vec::with_capacity(raw_items.len());
+
+    for raw_item in raw_items {
+        match raw_item {
+            raw::LetItem::Declaration {
+                label_span,
+                label,
+                term: Embed(raw_term),
+            } => {
+                forward_declarations.check_declaration(label_span, &label)?;
+                // Ensure that the declaration's type annotation is actually a type
+                let (term, _) = infer_universe(&env, &raw_term)?;
+                forward_declarations.insert_declaration(label_span, label, term);
+            },
+            raw::LetItem::Definition {
+                label_span,
+                label,
+                binder,
+                term: Embed(raw_term),
+            } => {
+                let (term, ty) = match forward_declarations.define(label_span, &label)? {
+                    Some(ann) => {
+                        let ty = nf_term(&env, &ann)?;
+                        let term = check_term(&env, &raw_term, &ty)?;
+                        (RcTerm::from(Term::Ann(term, ann)), ty)
+                    },
+                    None => infer_term(&env, &raw_term)?,
+                };
+
+                env.insert_declaration(binder.0.clone(), ty);
+                env.insert_definition(binder.0.clone(), term.clone());
+                definitions.push((binder.0, term));
+            },
+        }
+    }
+
+    // Unlike in modules, every declaration needs to have a definition
+    if let Some((declaration_span, label)) = forward_declarations.first_pending() {
+        return Err(TypeError::DeclarationWithoutDefinition {
+            declaration_span,
+            label,
+        });
+    }
+
+    Ok((env, definitions))
+}
+
 /// Ensures that the given term is a universe, returning the level of that
 /// universe and its elaborated form.
 fn infer_universe<Env>(env: &Env, raw_term: &raw::RcTerm) -> Result<(RcTerm, Level), TypeError>
 where
-    Env: DeclarationEnv + DefinitionEnv,
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
 {
+    // Holes in type positions are assumed to be in the first universe
+    if let raw::Term::Hole(span) = *raw_term.inner {
+        let level = Level(0);
+        let meta = env.fresh_meta(span, RcValue::from(Value::Universe(level)));
+        return Ok((RcTerm::from(Term::Meta(meta)), level));
+    }
+
     let (term, ty) = infer_term(env, raw_term)?;
-    match *ty {
+    match *force(env, &ty)? {
         Value::Universe(level) => Ok((term, level)),
         _ => Err(TypeError::ExpectedUniverse {
             span: raw_term.span(),
@@ -221,7 +371,7 @@ pub fn check_pattern<Env>(
     expected_ty: &RcType,
 ) -> Result<(RcPattern, Vec<(FreeVar<String>, RcType)>), TypeError>
 where
-    Env: DeclarationEnv + DefinitionEnv,
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
 {
     match (&*raw_pattern.inner, &*expected_ty.inner) {
         (&raw::Pattern::Binder(_, Binder(ref free_var)), _) => {
@@ -238,7 +388,7 @@ where
     }
 
     let (pattern, inferred_ty, declarations) = infer_pattern(env, raw_pattern)?;
-    if Type::term_eq(&inferred_ty, expected_ty) {
+    if unify(env, &inferred_ty, expected_ty)? {
         Ok((pattern, declarations))
     } else {
         Err(TypeError::Mismatch {
@@ -256,7 +406,7 @@ pub fn infer_pattern<Env>(
     raw_pattern: &raw::RcPattern,
 ) -> Result<(RcPattern, RcType, Vec<(FreeVar<String>, RcType)>), TypeError>
 where
-    Env: DeclarationEnv + DefinitionEnv,
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
 {
     match *raw_pattern.inner {
         raw::Pattern::Ann(ref raw_pattern, Embed(ref raw_ty)) => {
@@ -274,10 +424,26 @@ where
             let (literal, ty) = infer_literal(literal)?;
             Ok((RcPattern::from(Pattern::Literal(literal)), ty, vec![]))
         },
+        raw::Pattern::Global(span, ref name) => {
+            let definition = env.get_global_definition(name).map(|value| &*value.inner);
+
+            match (definition, env.get_global_declaration(name)) {
+                (Some(Value::Literal(literal)), Some(ty)) => Ok((
+                    RcPattern::from(Pattern::Literal(literal.clone())),
+                    ty.clone(),
+                    vec![],
+                )),
+                (_, _) => Err(TypeError::UndefinedName {
+                    span,
+                    name: name.clone(),
+                }),
+            }
+        },
         raw::Pattern::Binder(span, ref binder) => Err(TypeError::BinderNeedsAnnotation {
             span,
             binder: binder.clone(),
         }),
+        raw::Pattern::Error(span) => Err(InternalError::UnexpectedParseError { span }.into()),
     }
 }
 
@@ -289,10 +455,14 @@ pub fn check_term<Env>(
     expected_ty: &RcType,
 ) -> Result<RcTerm, TypeError>
 where
-    Env: DeclarationEnv + DefinitionEnv,
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
 {
+    // Look through any metavariables that have been solved
+    let expected_ty = &force(env, expected_ty)?;
+
     match (&*raw_term.inner, &*expected_ty.inner) {
-        (&raw::Term::Literal(ref raw_literal), _) => {
+        // Literals checked against unsolved metavariables are left to C-CONV
+        (&raw::Term::Literal(ref raw_literal), _) if expected_ty.meta_app().is_none() => {
             let literal = check_literal(raw_literal, expected_ty)?;
             return Ok(RcTerm::from(Term::Literal(literal)));
         },
@@ -318,13 +488,29 @@ where
             // TODO: We might want to optimise for this case, rather than
             // falling through to `infer` and unbinding again at I-LAM
         },
-        (&raw::Term::Lam(_, _), _) => {
+        (&raw::Term::Lam(_, _), _) if expected_ty.meta_app().is_none() => {
             return Err(TypeError::UnexpectedFunction {
                 span: raw_term.span(),
                 expected: Box::new(expected_ty.resugar()),
             });
         },
 
+        // C-LET
+        (&raw::Term::Let(_, ref raw_scope), _) => {
+            let (raw_items, raw_body) = raw_scope.clone().unbind();
+
+            let (body_env, definitions) = check_let_items(env, raw_items.unnest())?;
+            let body = check_term(&body_env, &raw_body, expected_ty)?;
+
+            return Ok(definitions
+                .into_iter()
+                .rev()
+                .fold(body, |body, (free_var, term)| {
+                    let scope = Scope::new((Binder(free_var), Embed(term)), body);
+                    RcTerm::from(Term::Let(scope))
+                }));
+        },
+
         // C-IF
         (&raw::Term::If(_, ref raw_cond, ref raw_if_true, ref raw_if_false), _) => {
             let bool_ty = RcValue::from(Value::global("Bool"));
@@ -375,15 +561,16 @@ where
             return Ok(RcTerm::from(Term::Record(Scope::new(fields, ()))));
         },
 
-        (&raw::Term::Case(_, ref raw_head, ref raw_clauses), _) => {
+        (&raw::Term::Case(span, ref raw_head, ref raw_clauses), _) => {
             let (head, head_ty) = infer_term(env, raw_head)?;
+            let mut patterns = Vec::with_capacity(raw_clauses.len());
 
-            // TODO: ensure that patterns are exhaustive
             let clauses = raw_clauses
                 .iter()
                 .map(|raw_clause| {
                     let (raw_pattern, raw_body) = raw_clause.clone().unbind();
                     let (pattern, declarations) = check_pattern(env, &raw_pattern, &head_ty)?;
+                    patterns.push((raw_pattern.span(), pattern.clone()));
 
                     let mut body_env = env.clone();
                     body_env.extend_declarations(declarations);
@@ -392,6 +579,8 @@ where
                     Ok(Scope::new(pattern, body))
                 }).collect::<Result<_, TypeError>>()?;
 
+            check_coverage(span, &patterns)?;
+
             return Ok(RcTerm::from(Term::Case(head, clauses)));
         },
 
@@ -419,9 +608,10 @@ where
             Some(_) | None => unimplemented!(),
         },
 
+        // C-HOLE
         (&raw::Term::Hole(span), _) => {
-            let expected = Some(Box::new(expected_ty.resugar()));
-            return Err(TypeError::UnableToElaborateHole { span, expected });
+            let meta = env.fresh_meta(span, expected_ty.clone());
+            return Ok(RcTerm::from(Term::Meta(meta)));
         },
 
         _ => {},
@@ -429,7 +619,7 @@ where
 
     // C-CONV
     let (term, inferred_ty) = infer_term(env, raw_term)?;
-    if Type::term_eq(&inferred_ty, expected_ty) {
+    if unify(env, &inferred_ty, expected_ty)? {
         Ok(term)
     } else {
         Err(TypeError::Mismatch {
@@ -444,7 +634,7 @@ where
 /// inferred type if successful
 pub fn infer_term<Env>(env: &Env, raw_term: &raw::RcTerm) -> Result<(RcTerm, RcType), TypeError>
 where
-    Env: DeclarationEnv + DefinitionEnv,
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
 {
     use std::cmp;
 
@@ -564,6 +754,24 @@ where
             ))
         },
 
+        // I-LET
+        raw::Term::Let(_, ref raw_scope) => {
+            let (raw_items, raw_body) = raw_scope.clone().unbind();
+
+            let (body_env, definitions) = check_let_items(env, raw_items.unnest())?;
+            let (body, body_ty) = infer_term(&body_env, &raw_body)?;
+
+            // Ensure that the bindings do not escape via the inferred type
+            let mut body_ty = RcTerm::from(&*body_ty);
+            let mut body = body;
+            for (free_var, term) in definitions.into_iter().rev() {
+                body_ty = body_ty.substs(&[(free_var.clone(), term.clone())]);
+                body = RcTerm::from(Term::Let(Scope::new((Binder(free_var), Embed(term)), body)));
+            }
+
+            Ok((body, nf_term(env, &body_ty)?))
+        },
+
         // I-IF
         raw::Term::If(_, ref raw_cond, ref raw_if_true, ref raw_if_false) => {
             let bool_ty = RcValue::from(Value::global("Bool"));
@@ -577,6 +785,7 @@ where
         // I-APP
         raw::Term::App(ref raw_head, ref raw_arg) => {
             let (head, head_ty) = infer_term(env, raw_head)?;
+            let head_ty = force(env, &head_ty)?;
 
             match *head_ty {
                 Value::Pi(ref scope) => {
@@ -638,6 +847,7 @@ where
         // I-PROJ
         raw::Term::Proj(_, ref expr, label_span, ref label) => {
             let (expr, ty) = infer_term(env, expr)?;
+            let ty = force(env, &ty)?;
 
             if let Value::RecordType(ref scope) = *ty.inner {
                 let (fields, ()) = scope.clone().unbind();
@@ -668,14 +878,15 @@ where
         // I-CASE
         raw::Term::Case(span, ref raw_head, ref raw_clauses) => {
             let (head, head_ty) = infer_term(env, raw_head)?;
+            let mut patterns = Vec::with_capacity(raw_clauses.len());
             let mut ty = None;
 
-            // TODO: ensure that patterns are exhaustive
             let clauses = raw_clauses
                 .iter()
                 .map(|raw_clause| {
                     let (raw_pattern, raw_body) = raw_clause.clone().unbind();
                     let (pattern, declarations) = check_pattern(env, &raw_pattern, &head_ty)?;
+                    patterns.push((raw_pattern.span(), pattern.clone()));
 
                     let (body, body_ty) = {
                         let mut body_env = env.clone();
@@ -685,7 +896,7 @@ where
 
                     match ty {
                         None => ty = Some(body_ty),
-                        Some(ref ty) if RcValue::term_eq(&body_ty, ty) => {},
+                        Some(ref ty) if unify(env, &body_ty, ty)? => {},
                         Some(ref ty) => {
                             return Err(TypeError::Mismatch {
                                 span: raw_body.span(),
@@ -699,11 +910,18 @@ where
                 }).collect::<Result<_, TypeError>>()?;
 
             match ty {
-                Some(ty) => Ok((RcTerm::from(Term::Case(head, clauses)), ty)),
+                Some(ty) => {
+                    check_coverage(span, &patterns)?;
+                    Ok((RcTerm::from(Term::Case(head, clauses)), ty))
+                },
                 None => Err(TypeError::AmbiguousEmptyCase { span }),
             }
         },
 
         raw::Term::Array(span, _) => Err(TypeError::AmbiguousArrayLiteral { span }),
+
+        // Terms that failed to parse should have been reported before we
+        // attempted to type check them
+        raw::Term::Error(span) => Err(InternalError::UnexpectedParseError { span }.into()),
     }
 }
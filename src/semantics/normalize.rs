@@ -4,17 +4,44 @@ use syntax::core::{
     Head, Literal, Neutral, Pattern, RcNeutral, RcPattern, RcTerm, RcValue, Term, Value,
 };
 
-use semantics::errors::InternalError;
-use semantics::DefinitionEnv;
+use semantics::errors::{InternalError, NormalizeError};
+use semantics::{DefinitionEnv, MetaEnv};
 
 /// Reduce a term to its normal form
-pub fn nf_term<Env>(env: &Env, term: &RcTerm) -> Result<RcValue, InternalError>
+///
+/// If the environment has a fuel limit, this returns an error once more than
+/// that many reduction steps have been taken.
+pub fn nf_term<Env>(env: &Env, term: &RcTerm) -> Result<RcValue, NormalizeError>
 where
-    Env: DefinitionEnv,
+    Env: DefinitionEnv + MetaEnv,
+{
+    nf_term_fuel(env, &mut Fuel(env.get_eval_fuel()), term)
+}
+
+/// The number of reduction steps that remain, or `None` if evaluation is
+/// unlimited
+struct Fuel(Option<u64>);
+
+impl Fuel {
+    fn consume(&mut self) -> Result<(), NormalizeError> {
+        match self.0 {
+            None => Ok(()),
+            Some(0) => Err(NormalizeError::OutOfFuel),
+            Some(ref mut fuel) => {
+                *fuel -= 1;
+                Ok(())
+            },
+        }
+    }
+}
+
+fn nf_term_fuel<Env>(env: &Env, fuel: &mut Fuel, term: &RcTerm) -> Result<RcValue, NormalizeError>
+where
+    Env: DefinitionEnv + MetaEnv,
 {
     match *term.inner {
         // E-ANN
-        Term::Ann(ref expr, _) => nf_term(env, expr),
+        Term::Ann(ref expr, _) => nf_term_fuel(env, fuel, expr),
 
         // E-TYPE
         Term::Universe(level) => Ok(RcValue::from(Value::Universe(level))),
@@ -24,7 +51,10 @@ where
         // E-VAR, E-VAR-DEF
         Term::Var(ref var) => match *var {
             Var::Free(ref name) => match env.get_definition(name) {
-                Some(term) => nf_term(env, term),
+                Some(term) => {
+                    fuel.consume()?;
+                    nf_term_fuel(env, fuel, term)
+                },
                 None => Ok(RcValue::from(Value::from(var.clone()))),
             },
 
@@ -34,11 +64,11 @@ where
             Var::Bound(_) => Err(InternalError::UnexpectedBoundVar {
                 span: None,
                 var: var.clone(),
-            }),
+            }.into()),
         },
 
         Term::Extern(ref name, ref ty) => Ok(RcValue::from(Value::from(Neutral::Head(
-            Head::Extern(name.clone(), nf_term(env, ty)?),
+            Head::Extern(name.clone(), nf_term_fuel(env, fuel, ty)?),
         )))),
 
         Term::Global(ref name) => match env.get_global_definition(name.as_str()) {
@@ -46,13 +76,23 @@ where
             None => Ok(RcValue::from(Value::global(name.clone()))),
         },
 
+        // E-META, E-META-SOLVED
+        Term::Meta(ref meta) => match env.get_meta_solution(meta) {
+            // The solution might refer to metavariables that have since been
+            // solved, so we normalize it again
+            Some(solution) => nf_term_fuel(env, fuel, &RcTerm::from(Term::from(&*solution))),
+            None => Ok(RcValue::from(Value::from(Neutral::Head(Head::Meta(
+                meta.clone(),
+            ))))),
+        },
+
         // E-PI
         Term::Pi(ref scope) => {
             let ((name, Embed(ann)), body) = scope.clone().unbind();
 
             Ok(RcValue::from(Value::Pi(Scope::new(
-                (name, Embed(nf_term(env, &ann)?)),
-                nf_term(env, &body)?,
+                (name, Embed(nf_term_fuel(env, fuel, &ann)?)),
+                nf_term_fuel(env, fuel, &body)?,
             ))))
         },
 
@@ -61,21 +101,22 @@ where
             let ((name, Embed(ann)), body) = scope.clone().unbind();
 
             Ok(RcValue::from(Value::Lam(Scope::new(
-                (name, Embed(nf_term(env, &ann)?)),
-                nf_term(env, &body)?,
+                (name, Embed(nf_term_fuel(env, fuel, &ann)?)),
+                nf_term_fuel(env, fuel, &body)?,
             ))))
         },
 
         // E-APP
         Term::App(ref head, ref arg) => {
-            match *nf_term(env, head)?.inner {
+            match *nf_term_fuel(env, fuel, head)?.inner {
                 Value::Lam(ref scope) => {
                     // FIXME: do a local unbind here
                     let ((Binder(free_var), Embed(_)), body) = scope.clone().unbind();
-                    nf_term(env, &body.substs(&[(free_var, arg.clone())]))
+                    fuel.consume()?;
+                    nf_term_fuel(env, fuel, &body.substs(&[(free_var, arg.clone())]))
                 },
                 Value::Neutral(ref neutral, ref spine) => {
-                    let arg = nf_term(env, arg)?;
+                    let arg = nf_term_fuel(env, fuel, arg)?;
                     let mut spine = spine.clone();
 
                     match *neutral.inner {
@@ -97,6 +138,7 @@ where
                         },
                         Neutral::Head(Head::Var(_))
                         | Neutral::Head(Head::Global(_))
+                        | Neutral::Head(Head::Meta(_))
                         | Neutral::If(_, _, _)
                         | Neutral::Proj(_, _)
                         | Neutral::Case(_, _) => spine.push(arg),
@@ -104,26 +146,33 @@ where
 
                     Ok(RcValue::from(Value::Neutral(neutral.clone(), spine)))
                 },
-                _ => Err(InternalError::ArgumentAppliedToNonFunction),
+                _ => Err(InternalError::ArgumentAppliedToNonFunction.into()),
             }
         },
 
+        // E-LET
+        Term::Let(ref scope) => {
+            let ((Binder(free_var), Embed(term)), body) = scope.clone().unbind();
+            fuel.consume()?;
+            nf_term_fuel(env, fuel, &body.substs(&[(free_var, term)]))
+        },
+
         // E-IF, E-IF-TRUE, E-IF-FALSE
         Term::If(ref cond, ref if_true, ref if_false) => {
-            let value_cond = nf_term(env, cond)?;
+            let value_cond = nf_term_fuel(env, fuel, cond)?;
 
             match *value_cond {
-                Value::Literal(Literal::Bool(true)) => nf_term(env, if_true),
-                Value::Literal(Literal::Bool(false)) => nf_term(env, if_false),
+                Value::Literal(Literal::Bool(true)) => nf_term_fuel(env, fuel, if_true),
+                Value::Literal(Literal::Bool(false)) => nf_term_fuel(env, fuel, if_false),
                 Value::Neutral(ref cond, ref spine) => Ok(RcValue::from(Value::Neutral(
                     RcNeutral::from(Neutral::If(
                         cond.clone(),
-                        nf_term(env, if_true)?,
-                        nf_term(env, if_false)?,
+                        nf_term_fuel(env, fuel, if_true)?,
+                        nf_term_fuel(env, fuel, if_false)?,
                     )),
                     spine.clone(),
                 ))),
-                _ => Err(InternalError::ExpectedBoolExpr),
+                _ => Err(InternalError::ExpectedBoolExpr.into()),
             }
         },
 
@@ -135,8 +184,8 @@ where
                     .unnest()
                     .into_iter()
                     .map(|(label, binder, Embed(ann))| {
-                        Ok((label, binder, Embed(nf_term(env, &ann)?)))
-                    }).collect::<Result<_, _>>()?,
+                        Ok((label, binder, Embed(nf_term_fuel(env, fuel, &ann)?)))
+                    }).collect::<Result<_, NormalizeError>>()?,
             );
 
             Ok(RcValue::from(Value::RecordType(Scope::new(fields, ()))))
@@ -150,8 +199,8 @@ where
                     .unnest()
                     .into_iter()
                     .map(|(label, binder, Embed(term))| {
-                        Ok((label, binder, Embed(nf_term(env, &term)?)))
-                    }).collect::<Result<_, _>>()?,
+                        Ok((label, binder, Embed(nf_term_fuel(env, fuel, &term)?)))
+                    }).collect::<Result<_, NormalizeError>>()?,
             );
 
             Ok(RcValue::from(Value::Record(Scope::new(fields, ()))))
@@ -159,7 +208,7 @@ where
 
         // E-PROJ
         Term::Proj(ref expr, ref label) => {
-            match *nf_term(env, expr)? {
+            match *nf_term_fuel(env, fuel, expr)? {
                 Value::Neutral(ref neutral, ref spine) => {
                     return Ok(RcValue::from(Value::Neutral(
                         RcNeutral::from(Neutral::Proj(neutral.clone(), label.clone())),
@@ -181,12 +230,12 @@ where
 
             Err(InternalError::ProjectedOnNonExistentField {
                 label: label.clone(),
-            })
+            }.into())
         },
 
         // E-CASE
         Term::Case(ref head, ref clauses) => {
-            let head = nf_term(env, head)?;
+            let head = nf_term_fuel(env, fuel, head)?;
 
             if let Value::Neutral(ref neutral, ref spine) = *head {
                 Ok(RcValue::from(Value::Neutral(
@@ -196,8 +245,8 @@ where
                             .iter()
                             .map(|clause| {
                                 let (pattern, body) = clause.clone().unbind();
-                                Ok(Scope::new(pattern, nf_term(env, &body)?))
-                            }).collect::<Result<_, _>>()?,
+                                Ok(Scope::new(pattern, nf_term_fuel(env, fuel, &body)?))
+                            }).collect::<Result<_, NormalizeError>>()?,
                     )),
                     spine.clone(),
                 )))
@@ -209,10 +258,11 @@ where
                             .into_iter()
                             .map(|(free_var, value)| (free_var, RcTerm::from(&*value.inner)))
                             .collect::<Vec<_>>();
-                        return nf_term(env, &body.substs(&mappings));
+                        fuel.consume()?;
+                        return nf_term_fuel(env, fuel, &body.substs(&mappings));
                     }
                 }
-                Err(InternalError::NoPatternsApplicable)
+                Err(InternalError::NoPatternsApplicable.into())
             }
         },
 
@@ -220,8 +270,8 @@ where
         Term::Array(ref elems) => Ok(RcValue::from(Value::Array(
             elems
                 .iter()
-                .map(|elem| nf_term(env, elem))
-                .collect::<Result<_, _>>()?,
+                .map(|elem| nf_term_fuel(env, fuel, elem))
+                .collect::<Result<_, NormalizeError>>()?,
         ))),
     }
 }
@@ -127,3 +127,80 @@ fn duplicate_definitions() {
         Err(err) => panic!("unexpected error: {}", err),
     }
 }
+
+#[test]
+fn solve_holes() {
+    let mut codemap = CodeMap::new();
+    let writer = StandardStream::stdout(ColorChoice::Always);
+
+    let src = r#"
+        id : (a : Type) -> a -> a;
+        id a x = x;
+
+        hello : _;
+        hello = id _ "hello";
+    "#;
+
+    let raw_module = parse_module(&mut codemap, src);
+    if let Err(err) = check_module(&TcEnv::default(), &raw_module) {
+        codespan_reporting::emit(&mut writer.lock(), &codemap, &err.to_diagnostic()).unwrap();
+        panic!("type error!")
+    }
+}
+
+#[test]
+fn solve_holes_universe_mismatch() {
+    let mut codemap = CodeMap::new();
+
+    let src = "
+        id : (a : Type) -> a -> a;
+        id a x = x;
+
+        bad = id _ Type;
+    ";
+
+    let raw_module = parse_module(&mut codemap, src);
+    match check_module(&TcEnv::default(), &raw_module) {
+        Ok(_) => panic!("expected error"),
+        Err(TypeError::Mismatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+}
+
+// Solutions that refer to local variables are not supported yet
+#[test]
+fn solve_holes_local_var() {
+    let mut codemap = CodeMap::new();
+
+    let src = "
+        id : (a : Type) -> a -> a;
+        id a x = x;
+
+        f : (a : Type) -> a -> a;
+        f a x = id _ x;
+    ";
+
+    let raw_module = parse_module(&mut codemap, src);
+    match check_module(&TcEnv::default(), &raw_module) {
+        Ok(_) => panic!("expected error"),
+        Err(TypeError::Mismatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+}
+
+#[test]
+fn unsolved_hole() {
+    let mut codemap = CodeMap::new();
+
+    let src = "
+        foo : Type;
+        foo = _;
+    ";
+
+    let raw_module = parse_module(&mut codemap, src);
+    match check_module(&TcEnv::default(), &raw_module) {
+        Ok(_) => panic!("expected error"),
+        Err(TypeError::UnableToElaborateHole { .. }) => {},
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+}
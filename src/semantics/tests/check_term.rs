@@ -36,6 +36,18 @@ fn dependent_record_propagate_types() {
     parse_check_term(&mut codemap, &tc_env, given_expr, &expected_ty);
 }
 
+#[test]
+fn dependent_record_hole() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"Record { t : Type; x : t }";
+    let given_expr = r#"record { t = _; x = "hello" }"#;
+
+    let expected_ty = parse_nf_term(&mut codemap, &tc_env, expected_ty);
+    parse_check_term(&mut codemap, &tc_env, given_expr, &expected_ty);
+}
+
 #[test]
 fn case_expr() {
     let mut codemap = CodeMap::new();
@@ -93,10 +105,136 @@ fn case_expr_empty() {
     let expected_ty = r"String";
     let given_expr = r#"case "helloo" of {}"#;
 
+    let expected_ty = parse_nf_term(&mut codemap, &tc_env, expected_ty);
+    match check_term(&tc_env, &parse_term(&mut codemap, given_expr), &expected_ty) {
+        Err(TypeError::NonExhaustiveMatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(term) => panic!("expected error but found: {}", term),
+    }
+}
+
+#[test]
+fn case_expr_non_exhaustive() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"case "helloo" of {
+        "hi" => "haha";
+        "hello" => "byee";
+    }"#;
+
+    let expected_ty = parse_nf_term(&mut codemap, &tc_env, expected_ty);
+    match check_term(&tc_env, &parse_term(&mut codemap, given_expr), &expected_ty) {
+        Err(TypeError::NonExhaustiveMatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(term) => panic!("expected error but found: {}", term),
+    }
+}
+
+#[test]
+fn case_expr_unreachable_after_wildcard() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"case "helloo" of {
+        "hi" => "haha";
+        greeting => greeting;
+        "hello" => "byee";
+    }"#;
+
+    let expected_ty = parse_nf_term(&mut codemap, &tc_env, expected_ty);
+    match check_term(&tc_env, &parse_term(&mut codemap, given_expr), &expected_ty) {
+        Err(TypeError::UnreachableClause { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(term) => panic!("expected error but found: {}", term),
+    }
+}
+
+#[test]
+fn case_expr_unreachable_duplicate_literal() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"case "helloo" of {
+        "hi" => "haha";
+        "hi" => "byee";
+        greeting => greeting;
+    }"#;
+
+    let expected_ty = parse_nf_term(&mut codemap, &tc_env, expected_ty);
+    match check_term(&tc_env, &parse_term(&mut codemap, given_expr), &expected_ty) {
+        Err(TypeError::UnreachableClause { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(term) => panic!("expected error but found: {}", term),
+    }
+}
+
+#[test]
+fn case_expr_bool() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"case true of {
+        true => "hello";
+        false => "hi";
+    }"#;
+
     let expected_ty = parse_nf_term(&mut codemap, &tc_env, expected_ty);
     parse_check_term(&mut codemap, &tc_env, given_expr, &expected_ty);
 }
 
+#[test]
+fn case_expr_bool_non_exhaustive() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"case true of {
+        true => "hello";
+    }"#;
+
+    let expected_ty = parse_nf_term(&mut codemap, &tc_env, expected_ty);
+    match check_term(&tc_env, &parse_term(&mut codemap, given_expr), &expected_ty) {
+        Err(TypeError::NonExhaustiveMatch { missing, .. }) => {
+            assert_eq!(missing.to_string(), "false");
+        },
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(term) => panic!("expected error but found: {}", term),
+    }
+}
+
+#[test]
+fn if_() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"U8";
+    let given_expr = r#"if true then 1 else 2"#;
+
+    let expected_ty = parse_nf_term(&mut codemap, &tc_env, expected_ty);
+    parse_check_term(&mut codemap, &tc_env, given_expr, &expected_ty);
+}
+
+#[test]
+fn if_branch_mismatch() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"U8";
+    let given_expr = r#"if true then 1 else "hello""#;
+
+    let expected_ty = parse_nf_term(&mut codemap, &tc_env, expected_ty);
+    match check_term(&tc_env, &parse_term(&mut codemap, given_expr), &expected_ty) {
+        Err(TypeError::LiteralMismatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(term) => panic!("expected error but found: {}", term),
+    }
+}
+
 #[test]
 fn array_0_string() {
     let mut codemap = CodeMap::new();
@@ -151,3 +289,16 @@ fn array_elem_ty_mismatch() {
         Ok(term) => panic!("expected error but found: {}", term),
     }
 }
+
+#[test]
+fn global_declaration() {
+    let mut codemap = CodeMap::new();
+    let mut tc_env = TcEnv::empty();
+    tc_env.insert_global("U8", RcValue::from(Value::universe(0)), None);
+
+    let expected_ty = r"U8";
+    let given_expr = r"1";
+
+    let expected_ty = parse_nf_term(&mut codemap, &tc_env, expected_ty);
+    parse_check_term(&mut codemap, &tc_env, given_expr, &expected_ty);
+}
@@ -32,6 +32,42 @@ fn undefined_name() {
     );
 }
 
+#[test]
+fn undefined_name_empty_env() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::empty();
+
+    let given_expr = "U8";
+
+    assert_eq!(
+        infer_term(&tc_env, &parse_term(&mut codemap, given_expr)),
+        Err(TypeError::UndefinedName {
+            span: ByteSpan::new(ByteIndex(1), ByteIndex(3)),
+            name: String::from("U8"),
+        }),
+    );
+}
+
+#[test]
+fn global_definition() {
+    let mut codemap = CodeMap::new();
+    let mut tc_env = TcEnv::empty();
+    tc_env.insert_global("String", RcValue::from(Value::universe(0)), None);
+    tc_env.insert_global(
+        "greeting",
+        RcValue::from(Value::global("String")),
+        Some(RcValue::from(Value::Literal(Literal::String(String::from("hello"))))),
+    );
+
+    let expected_ty = r"String";
+    let given_expr = r"greeting";
+
+    assert_term_eq!(
+        parse_infer_term(&mut codemap, &tc_env, given_expr).1,
+        parse_nf_term(&mut codemap, &tc_env, expected_ty),
+    );
+}
+
 #[test]
 fn extern_not_found() {
     let mut codemap = CodeMap::new();
@@ -46,6 +82,34 @@ fn extern_not_found() {
     }
 }
 
+#[test]
+fn extern_() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String -> String -> String";
+    let given_expr = r#"extern "string-append" : String -> String -> String"#;
+
+    assert_term_eq!(
+        parse_infer_term(&mut codemap, &tc_env, given_expr).1,
+        parse_nf_term(&mut codemap, &tc_env, expected_ty),
+    );
+}
+
+#[test]
+fn extern_ann_not_a_type() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r#"extern "string-append" : "hello""#;
+
+    match infer_term(&tc_env, &parse_term(&mut codemap, given_expr)) {
+        Err(TypeError::ExpectedUniverse { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {}", term, ty),
+    }
+}
+
 #[test]
 fn ty() {
     let mut codemap = CodeMap::new();
@@ -367,7 +431,6 @@ fn case_expr_bool() {
 }
 
 #[test]
-#[ignore]
 fn case_expr_bool_bad() {
     let mut codemap = CodeMap::new();
     let tc_env = TcEnv::default();
@@ -413,6 +476,249 @@ fn case_expr_empty() {
     }
 }
 
+#[test]
+fn if_() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"if true then "hello" else "hi""#;
+
+    assert_term_eq!(
+        parse_infer_term(&mut codemap, &tc_env, given_expr).1,
+        parse_nf_term(&mut codemap, &tc_env, expected_ty),
+    );
+}
+
+#[test]
+fn if_branch_mismatch() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r#"if true then "hello" else String"#;
+
+    match infer_term(&tc_env, &parse_term(&mut codemap, given_expr)) {
+        Err(TypeError::Mismatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {}", term, ty),
+    }
+}
+
+#[test]
+fn if_cond_not_bool() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r#"if "true" then "hello" else "hi""#;
+
+    match infer_term(&tc_env, &parse_term(&mut codemap, given_expr)) {
+        Err(TypeError::LiteralMismatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {}", term, ty),
+    }
+}
+
+#[test]
+fn let_() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"let x = "hello"; in x"#;
+
+    assert_term_eq!(
+        parse_infer_term(&mut codemap, &tc_env, given_expr).1,
+        parse_nf_term(&mut codemap, &tc_env, expected_ty),
+    );
+}
+
+#[test]
+fn let_dependent() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"let a = String; x : a; x = "hello"; in x"#;
+
+    assert_term_eq!(
+        parse_infer_term(&mut codemap, &tc_env, given_expr).1,
+        parse_nf_term(&mut codemap, &tc_env, expected_ty),
+    );
+}
+
+#[test]
+fn let_id() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"let
+        id : (a : Type) -> a -> a;
+        id a x = x;
+    in
+        id String "hello""#;
+
+    assert_term_eq!(
+        parse_infer_term(&mut codemap, &tc_env, given_expr).1,
+        parse_nf_term(&mut codemap, &tc_env, expected_ty),
+    );
+}
+
+#[test]
+fn let_mismatch() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r#"let x : String; x = 'a'; in x"#;
+
+    match infer_term(&tc_env, &parse_term(&mut codemap, given_expr)) {
+        Err(TypeError::LiteralMismatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {}", term, ty),
+    }
+}
+
+#[test]
+fn let_declaration_followed_definition() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r#"let x = "a"; x : String; in x"#;
+
+    match infer_term(&tc_env, &parse_term(&mut codemap, given_expr)) {
+        Err(TypeError::DeclarationFollowedDefinition { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {}", term, ty),
+    }
+}
+
+#[test]
+fn let_duplicate_declarations() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r#"let x : String; x : U8; x = "a"; in x"#;
+
+    match infer_term(&tc_env, &parse_term(&mut codemap, given_expr)) {
+        Err(TypeError::DuplicateDeclarations { ref label, .. }) => {
+            assert_eq!(*label, Label(String::from("x")));
+        },
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {}", term, ty),
+    }
+}
+
+#[test]
+fn let_declaration_without_definition() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r#"let x : String; in x"#;
+
+    match infer_term(&tc_env, &parse_term(&mut codemap, given_expr)) {
+        Err(TypeError::DeclarationWithoutDefinition { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {}", term, ty),
+    }
+}
+
+#[test]
+fn let_declaration_without_definition_shadowed() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r#"let x = "a"; y : String; in x"#;
+
+    match infer_term(&tc_env, &parse_term(&mut codemap, given_expr)) {
+        Err(TypeError::DeclarationWithoutDefinition { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {}", term, ty),
+    }
+}
+
+#[test]
+fn hole_ann() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#""hello" : _"#;
+
+    assert_term_eq!(
+        parse_infer_term(&mut codemap, &tc_env, given_expr).1,
+        parse_nf_term(&mut codemap, &tc_env, expected_ty),
+    );
+}
+
+#[test]
+fn hole_app_id() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let expected_ty = r"String";
+    let given_expr = r#"let
+        id : (a : Type) -> a -> a;
+        id a x = x;
+    in
+        id _ "hello""#;
+
+    assert_term_eq!(
+        parse_infer_term(&mut codemap, &tc_env, given_expr).1,
+        parse_nf_term(&mut codemap, &tc_env, expected_ty),
+    );
+}
+
+#[test]
+fn hole_app_id_mismatch() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r#"let
+        id : (a : Type) -> a -> a;
+        id a x = x;
+    in
+        id _ "hello" : Char"#;
+
+    match infer_term(&tc_env, &parse_term(&mut codemap, given_expr)) {
+        Err(TypeError::Mismatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {}", term, ty),
+    }
+}
+
+#[test]
+fn hole_app_id_universe_mismatch() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r#"let
+        id : (a : Type) -> a -> a;
+        id a x = x;
+    in
+        id _ Type"#;
+
+    match infer_term(&tc_env, &parse_term(&mut codemap, given_expr)) {
+        Err(TypeError::Mismatch { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok((term, ty)) => panic!("expected error, found {} : {}", term, ty),
+    }
+}
+
+#[test]
+fn hole_unsolved() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    let given_expr = r"Record { x : _ }";
+
+    let (term, _) = infer_term(&tc_env, &parse_term(&mut codemap, given_expr)).unwrap();
+    match zonk_term(&tc_env, &term) {
+        Err(TypeError::UnableToElaborateHole { .. }) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(term) => panic!("expected error, found {}", term),
+    }
+}
+
 mod church_encodings {
     use super::*;
 
@@ -39,7 +39,10 @@ fn parse_term(codemap: &mut CodeMap, src: &str) -> raw::RcTerm {
 }
 
 fn parse_infer_term(codemap: &mut CodeMap, tc_env: &TcEnv, src: &str) -> (RcTerm, RcType) {
-    match infer_term(tc_env, &parse_term(codemap, src)) {
+    let result = infer_term(tc_env, &parse_term(codemap, src))
+        .and_then(|(term, ty)| Ok((zonk_term(tc_env, &term)?, zonk_value(tc_env, &ty)?)));
+
+    match result {
         Ok((term, ty)) => (term, ty),
         Err(error) => {
             let writer = StandardStream::stdout(ColorChoice::Always);
@@ -62,7 +65,10 @@ fn parse_nf_term(codemap: &mut CodeMap, tc_env: &TcEnv, src: &str) -> RcValue {
 }
 
 fn parse_check_term(codemap: &mut CodeMap, tc_env: &TcEnv, src: &str, expected: &RcType) {
-    match check_term(tc_env, &parse_term(codemap, src), expected) {
+    let result = check_term(tc_env, &parse_term(codemap, src), expected)
+        .and_then(|term| zonk_term(tc_env, &term));
+
+    match result {
         Ok(_) => {},
         Err(error) => {
             let writer = StandardStream::stdout(ColorChoice::Always);
@@ -76,3 +82,4 @@ mod check_module;
 mod check_term;
 mod infer_term;
 mod normalize;
+mod resugar;
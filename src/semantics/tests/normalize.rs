@@ -240,4 +240,119 @@ mod nf_term {
             parse_nf_term(&mut codemap, &tc_env, expected_expr),
         );
     }
+
+    #[test]
+    fn let_() {
+        let mut codemap = CodeMap::new();
+        let tc_env = TcEnv::default();
+
+        let given_expr = r#"let x = "hello"; y = x; in y"#;
+        let expected_expr = r#""hello""#;
+
+        assert_term_eq!(
+            parse_nf_term(&mut codemap, &tc_env, given_expr),
+            parse_nf_term(&mut codemap, &tc_env, expected_expr),
+        );
+    }
+
+    #[test]
+    fn let_nested() {
+        let mut codemap = CodeMap::new();
+        let tc_env = TcEnv::default();
+
+        let given_expr = r#"let x = "hello"; y = "world"; in x"#;
+        let expected_expr = r#"let x = "hello"; in let y = "world"; in x"#;
+
+        assert_term_eq!(
+            parse_nf_term(&mut codemap, &tc_env, given_expr),
+            parse_nf_term(&mut codemap, &tc_env, expected_expr),
+        );
+    }
+
+    #[test]
+    fn let_lam() {
+        let mut codemap = CodeMap::new();
+        let tc_env = TcEnv::default();
+
+        let given_expr = r#"let id (x : String) = x; in id"#;
+        let expected_expr = r#"\x : String => x"#;
+
+        assert_term_eq!(
+            parse_nf_term(&mut codemap, &tc_env, given_expr),
+            parse_nf_term(&mut codemap, &tc_env, expected_expr),
+        );
+    }
+
+    #[test]
+    fn case_bool() {
+        let mut codemap = CodeMap::new();
+        let tc_env = TcEnv::default();
+
+        let given_expr = r#"case false of { true => "hello"; false => "hi"; }"#;
+        let expected_expr = r#""hi""#;
+
+        assert_term_eq!(
+            parse_nf_term(&mut codemap, &tc_env, given_expr),
+            parse_nf_term(&mut codemap, &tc_env, expected_expr),
+        );
+    }
+
+    #[test]
+    fn global_definition() {
+        let mut codemap = CodeMap::new();
+        let mut tc_env = TcEnv::default();
+        // Names don't need to be known ahead of time
+        let name = String::from("greeting");
+        tc_env.insert_global(
+            name,
+            RcValue::from(Value::global("String")),
+            Some(RcValue::from(Value::Literal(Literal::String(String::from("hello"))))),
+        );
+
+        let given_expr = r#"greeting"#;
+        let expected_expr = r#""hello""#;
+
+        assert_term_eq!(
+            parse_nf_term(&mut codemap, &tc_env, given_expr),
+            parse_nf_term(&mut codemap, &tc_env, expected_expr),
+        );
+    }
+
+    #[test]
+    fn out_of_fuel() {
+        let mut codemap = CodeMap::new();
+        let mut tc_env = TcEnv::default();
+
+        let given_expr = r#"let x = "hello"; y = x; in y"#;
+        let term = parse_infer_term(&mut codemap, &tc_env, given_expr).0;
+        tc_env.set_eval_fuel(Some(1));
+
+        assert_eq!(nf_term(&tc_env, &term), Err(NormalizeError::OutOfFuel));
+    }
+
+    #[test]
+    fn out_of_fuel_message() {
+        use codespan_reporting::Severity;
+
+        let err = TypeError::from(NormalizeError::OutOfFuel);
+
+        assert_eq!(err.to_string(), "Evaluation ran out of fuel.");
+        assert_eq!(err.to_diagnostic().severity, Severity::Error);
+    }
+
+    #[test]
+    fn enough_fuel() {
+        let mut codemap = CodeMap::new();
+        let mut tc_env = TcEnv::default();
+
+        let given_expr = r#"let x = "hello"; y = x; in y"#;
+        let expected_expr = r#""hello""#;
+        let term = parse_infer_term(&mut codemap, &tc_env, given_expr).0;
+        tc_env.set_eval_fuel(Some(2));
+
+        assert_term_eq!(
+            nf_term(&tc_env, &term).unwrap(),
+            parse_nf_term(&mut codemap, &tc_env, expected_expr),
+        );
+    }
 }
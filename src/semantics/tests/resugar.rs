@@ -0,0 +1,123 @@
+use syntax::pretty::{ToDoc, FALLBACK_WIDTH};
+use syntax::translation::Resugar;
+
+use super::*;
+
+fn parse_resugar_term(codemap: &mut CodeMap, tc_env: &TcEnv, src: &str) -> (String, String) {
+    let (term, ty) = parse_infer_term(codemap, tc_env, src);
+
+    (
+        format!("{}", term.resugar().to_doc().group().pretty(FALLBACK_WIDTH)),
+        format!("{}", ty.resugar().to_doc().group().pretty(FALLBACK_WIDTH)),
+    )
+}
+
+#[test]
+fn universe() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    assert_eq!(
+        parse_resugar_term(&mut codemap, &tc_env, r"Type 1"),
+        (String::from("Type 1"), String::from("Type 2")),
+    );
+}
+
+#[test]
+fn universe_arg() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    assert_eq!(
+        parse_resugar_term(
+            &mut codemap,
+            &tc_env,
+            r"\(f : Type 2 -> Type) => f (Type 1)",
+        ).0,
+        r"\(f : Type 2 -> Type) => f (Type 1)",
+    );
+}
+
+#[test]
+fn pi() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    assert_eq!(
+        parse_resugar_term(&mut codemap, &tc_env, r"(a : Type) -> a -> a"),
+        (String::from("(a : Type) -> a -> a"), String::from("Type 1")),
+    );
+}
+
+#[test]
+fn arrow_arg() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    assert_eq!(
+        parse_resugar_term(&mut codemap, &tc_env, r"(Type -> Type) -> Type").0,
+        r"(Type -> Type) -> Type",
+    );
+}
+
+#[test]
+fn lam() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    assert_eq!(
+        parse_resugar_term(&mut codemap, &tc_env, r"\(a : Type) (x : a) => x"),
+        (
+            String::from(r"\(a : Type) (x : a) => x"),
+            String::from("(a : Type) (x : a) -> a"),
+        ),
+    );
+}
+
+#[test]
+fn lam_shadowed() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    assert_eq!(
+        parse_resugar_term(&mut codemap, &tc_env, r"\(x : Type) => \(x : Type) => x").0,
+        r"\(x x1 : Type) => x1",
+    );
+}
+
+#[test]
+fn app_nested() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    assert_eq!(
+        parse_resugar_term(
+            &mut codemap,
+            &tc_env,
+            r"\(f : Type -> Type -> Type) (a : Type) => f (f a a) a",
+        ).0,
+        r"\(f : Type -> Type -> Type) (a : Type) => f (f a a) a",
+    );
+}
+
+#[test]
+fn record_type_dependent() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    assert_eq!(
+        parse_resugar_term(&mut codemap, &tc_env, r"Record { t : Type; x : t }").0,
+        r"Record { t : Type; x : t }",
+    );
+}
+
+#[test]
+fn case() {
+    let mut codemap = CodeMap::new();
+    let tc_env = TcEnv::default();
+
+    assert_eq!(
+        parse_resugar_term(&mut codemap, &tc_env, r"\(x : U8) => case x of { y => y; }").0,
+        "\\(x : U8) => case x of {\n    y => y;\n}",
+    );
+}
@@ -0,0 +1,514 @@
+//! Solving and substituting metavariables
+
+use moniker::{Binder, BoundTerm, Embed, FreeVar, Nest, Scope, Var};
+use std::cmp;
+
+use syntax::core::{
+    Head, Literal, Neutral, Pattern, RcNeutral, RcPattern, RcTerm, RcType, RcValue, Term, Value,
+};
+use syntax::translation::Resugar;
+use syntax::{Label, Level};
+
+use semantics::errors::{NormalizeError, TypeError};
+use semantics::{nf_term, DeclarationEnv, DefinitionEnv, MetaEnv};
+
+/// The scope of a term that binds a single variable, like a lambda or a pi type
+type BinderScope = Scope<(Binder<String>, Embed<RcTerm>), RcTerm>;
+
+/// The scope of the fields in a record or record type
+type FieldsScope = Scope<Nest<(Label, Binder<String>, Embed<RcTerm>)>, ()>;
+
+/// Replace a solved metavariable at the head of a value with its solution
+pub fn force<Env>(env: &Env, value: &RcValue) -> Result<RcValue, NormalizeError>
+where
+    Env: DefinitionEnv + MetaEnv,
+{
+    match value.meta_app() {
+        Some((meta, _)) if env.get_meta_solution(meta).is_some() => {
+            nf_term(env, &RcTerm::from(Term::from(&**value)))
+        },
+        Some(_) | None => Ok(value.clone()),
+    }
+}
+
+/// Check that two values are equal, solving any metavariables that we find
+/// along the way
+///
+/// For now we only handle the simplest cases:
+///
+/// - metavariables can only be solved if they are not applied to any arguments
+/// - solutions can't refer to local variables, because they might escape the
+///   scope that they were bound in
+/// - metavariables under stuck `if` and `case` expressions are not solved
+/// - solutions must have a type that we can synthesize, and that type must
+///   unify with the type of the metavariable
+///
+/// Anything else falls back to alpha equality.
+pub fn unify<Env>(env: &Env, left: &RcValue, right: &RcValue) -> Result<bool, NormalizeError>
+where
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
+{
+    let left = force(env, left)?;
+    let right = force(env, right)?;
+
+    if RcValue::term_eq(&left, &right) {
+        return Ok(true);
+    }
+
+    match (left.meta_app(), right.meta_app()) {
+        (Some((meta, spine)), _) if spine.is_empty() => return solve(env, meta, &right),
+        (_, Some((meta, spine))) if spine.is_empty() => return solve(env, meta, &left),
+        (_, _) => {},
+    }
+
+    match (&*left.inner, &*right.inner) {
+        (Value::Pi(left_scope), Value::Pi(right_scope))
+        | (Value::Lam(left_scope), Value::Lam(right_scope)) => {
+            let ((_, Embed(left_ann)), left_body, (_, Embed(right_ann)), right_body) =
+                Scope::unbind2(left_scope.clone(), right_scope.clone());
+
+            Ok(unify(env, &left_ann, &right_ann)? && unify(env, &left_body, &right_body)?)
+        },
+        (Value::RecordType(left_scope), Value::RecordType(right_scope))
+        | (Value::Record(left_scope), Value::Record(right_scope)) => {
+            if left_scope.unsafe_pattern.unsafe_patterns.len()
+                != right_scope.unsafe_pattern.unsafe_patterns.len()
+            {
+                return Ok(false);
+            }
+
+            let (left_fields, (), right_fields, ()) =
+                Scope::unbind2(left_scope.clone(), right_scope.clone());
+            let fields = <_>::zip(left_fields.unnest().into_iter(), right_fields.unnest());
+
+            for ((left_label, _, Embed(left_ann)), (right_label, _, Embed(right_ann))) in fields {
+                if left_label != right_label || !unify(env, &left_ann, &right_ann)? {
+                    return Ok(false);
+                }
+            }
+
+            Ok(true)
+        },
+        (Value::Array(left_elems), Value::Array(right_elems)) => {
+            unify_all(env, left_elems, right_elems)
+        },
+        (Value::Neutral(left_neutral, left_spine), Value::Neutral(right_neutral, right_spine)) => {
+            Ok(left_neutral.term_eq(right_neutral) && unify_all(env, left_spine, right_spine)?)
+        },
+        (_, _) => Ok(false),
+    }
+}
+
+/// Unify each of the corresponding values in the given slices
+fn unify_all<Env>(env: &Env, left: &[RcValue], right: &[RcValue]) -> Result<bool, NormalizeError>
+where
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
+{
+    if left.len() != right.len() {
+        return Ok(false);
+    }
+
+    for (left, right) in <_>::zip(left.iter(), right.iter()) {
+        if !unify(env, left, right)? {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Attempt to solve a metavariable with the given value, returning `true` if
+/// the solution was accepted
+fn solve<Env>(env: &Env, meta: &FreeVar<String>, solution: &RcValue) -> Result<bool, NormalizeError>
+where
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
+{
+    if !solution.free_vars().is_empty() || occurs(meta, solution) {
+        return Ok(false);
+    }
+
+    let (_, meta_ty) = env
+        .get_meta_declaration(meta)
+        .expect("metavariable without a declaration");
+
+    match infer_value(env, solution)? {
+        Some(ref solution_ty) if unify(env, solution_ty, &meta_ty)? => {},
+        Some(_) | None => return Ok(false),
+    }
+
+    env.insert_meta_solution(meta.clone(), solution.clone());
+    Ok(true)
+}
+
+/// Synthesize the type of a candidate solution, returning `None` if we don't
+/// know how to
+///
+/// Solutions never contain local variables, so the only variables we
+/// encounter are the ones bound inside the solution itself.
+fn infer_value<Env>(env: &Env, value: &RcValue) -> Result<Option<RcType>, NormalizeError>
+where
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
+{
+    match *value.inner {
+        Value::Universe(level) => Ok(Some(RcValue::from(Value::Universe(level.succ())))),
+        Value::Literal(ref literal) => Ok(Some(literal_ty(literal))),
+        Value::Pi(ref scope) => {
+            let ((Binder(free_var), Embed(ann)), body) = scope.clone().unbind();
+
+            let ann_level = match infer_value_universe(env, &ann)? {
+                Some(ann_level) => ann_level,
+                None => return Ok(None),
+            };
+            let body_level = {
+                let mut body_env = env.clone();
+                body_env.insert_declaration(free_var, ann);
+                match infer_value_universe(&body_env, &body)? {
+                    Some(body_level) => body_level,
+                    None => return Ok(None),
+                }
+            };
+
+            Ok(Some(RcValue::from(Value::Universe(cmp::max(
+                ann_level, body_level,
+            )))))
+        },
+        Value::Lam(ref scope) => {
+            let ((Binder(free_var), Embed(ann)), body) = scope.clone().unbind();
+
+            let body_ty = {
+                let mut body_env = env.clone();
+                body_env.insert_declaration(free_var.clone(), ann.clone());
+                match infer_value(&body_env, &body)? {
+                    Some(body_ty) => body_ty,
+                    None => return Ok(None),
+                }
+            };
+
+            Ok(Some(RcValue::from(Value::Pi(Scope::new(
+                (Binder(free_var), Embed(ann)),
+                body_ty,
+            )))))
+        },
+        Value::RecordType(ref scope) => {
+            let (fields, ()) = scope.clone().unbind();
+            let mut env = env.clone();
+            let mut max_level = Level(0);
+
+            for (_, Binder(free_var), Embed(ann)) in fields.unnest() {
+                match infer_value_universe(&env, &ann)? {
+                    Some(ann_level) => max_level = cmp::max(max_level, ann_level),
+                    None => return Ok(None),
+                }
+                env.insert_declaration(free_var, ann);
+            }
+
+            Ok(Some(RcValue::from(Value::Universe(max_level))))
+        },
+        Value::Record(ref scope) => {
+            let (fields, ()) = scope.clone().unbind();
+            let mut env = env.clone();
+            let mut ty_fields = Vec::with_capacity(fields.unsafe_patterns.len());
+
+            for (label, Binder(free_var), Embed(expr)) in fields.unnest() {
+                let expr_ty = match infer_value(&env, &expr)? {
+                    Some(expr_ty) => expr_ty,
+                    None => return Ok(None),
+                };
+                env.insert_declaration(free_var.clone(), expr_ty.clone());
+                ty_fields.push((label, Binder(free_var), Embed(expr_ty)));
+            }
+
+            Ok(Some(RcValue::from(Value::RecordType(Scope::new(
+                Nest::new(ty_fields),
+                (),
+            )))))
+        },
+        Value::Array(ref elems) => {
+            // We can't synthesize the element type of an empty array
+            let elem_ty = match elems.first() {
+                Some(elem) => match infer_value(env, elem)? {
+                    Some(elem_ty) => elem_ty,
+                    None => return Ok(None),
+                },
+                None => return Ok(None),
+            };
+
+            for elem in &elems[1..] {
+                match infer_value(env, elem)? {
+                    Some(ref ty) if unify(env, ty, &elem_ty)? => {},
+                    Some(_) | None => return Ok(None),
+                }
+            }
+
+            let len = RcValue::from(Value::Literal(Literal::U64(elems.len() as u64)));
+            Ok(Some(RcValue::from(Value::Neutral(
+                RcNeutral::from(Neutral::global("Array")),
+                vec![len, elem_ty],
+            ))))
+        },
+        Value::Neutral(ref neutral, ref spine) => {
+            let mut head_ty = match infer_neutral(env, neutral)? {
+                Some(head_ty) => head_ty,
+                None => return Ok(None),
+            };
+
+            for arg in spine {
+                head_ty = match *force(env, &head_ty)?.inner {
+                    Value::Pi(ref scope) => {
+                        let ((Binder(free_var), Embed(ann)), body) = scope.clone().unbind();
+
+                        match infer_value(env, arg)? {
+                            Some(ref arg_ty) if unify(env, arg_ty, &ann)? => {},
+                            Some(_) | None => return Ok(None),
+                        }
+
+                        nf_term(env, &body.substs(&[(free_var, RcTerm::from(&**arg))]))?
+                    },
+                    _ => return Ok(None),
+                };
+            }
+
+            Ok(Some(head_ty))
+        },
+    }
+}
+
+/// Synthesize the type of a neutral value, returning `None` if we don't know
+/// how to
+fn infer_neutral<Env>(env: &Env, neutral: &Neutral) -> Result<Option<RcType>, NormalizeError>
+where
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
+{
+    match *neutral {
+        Neutral::Head(Head::Var(Var::Free(ref free_var))) => {
+            Ok(env.get_declaration(free_var).cloned())
+        },
+        Neutral::Head(Head::Var(Var::Bound(_))) => Ok(None),
+        Neutral::Head(Head::Global(ref name)) => Ok(env.get_global_declaration(name).cloned()),
+        Neutral::Head(Head::Extern(_, ref ty)) => Ok(Some(ty.clone())),
+        Neutral::Head(Head::Meta(ref meta)) => Ok(env.get_meta_declaration(meta).map(|(_, ty)| ty)),
+        Neutral::If(_, ref if_true, _) => infer_value(env, if_true),
+        Neutral::Proj(ref expr, ref label) => {
+            let ty = match infer_neutral(env, expr)? {
+                Some(ty) => force(env, &ty)?,
+                None => return Ok(None),
+            };
+
+            if let Value::RecordType(ref scope) = *ty.inner {
+                let (fields, ()) = scope.clone().unbind();
+                let expr = RcTerm::from(&**expr);
+                let mut mappings = vec![];
+
+                for (current_label, Binder(free_var), Embed(current_ann)) in fields.unnest() {
+                    if current_label == *label {
+                        return Ok(Some(nf_term(env, &current_ann.substs(&mappings))?));
+                    } else {
+                        mappings.push((
+                            free_var,
+                            RcTerm::from(Term::Proj(expr.clone(), current_label)),
+                        ));
+                    }
+                }
+            }
+
+            Ok(None)
+        },
+        Neutral::Case(_, _) => Ok(None),
+    }
+}
+
+/// Synthesize the universe level of a candidate solution, returning `None` if
+/// it is not a type that we know how to synthesize
+fn infer_value_universe<Env>(env: &Env, value: &RcValue) -> Result<Option<Level>, NormalizeError>
+where
+    Env: DeclarationEnv + DefinitionEnv + MetaEnv,
+{
+    match infer_value(env, value)? {
+        Some(ty) => match *force(env, &ty)?.inner {
+            Value::Universe(level) => Ok(Some(level)),
+            _ => Ok(None),
+        },
+        None => Ok(None),
+    }
+}
+
+/// The type of a literal
+fn literal_ty(literal: &Literal) -> RcType {
+    RcValue::from(Value::global(match *literal {
+        Literal::Bool(_) => "Bool",
+        Literal::String(_) => "String",
+        Literal::Char(_) => "Char",
+        Literal::U8(_) => "U8",
+        Literal::U16(_) => "U16",
+        Literal::U32(_) => "U32",
+        Literal::U64(_) => "U64",
+        Literal::I8(_) => "I8",
+        Literal::I16(_) => "I16",
+        Literal::I32(_) => "I32",
+        Literal::I64(_) => "I64",
+        Literal::F32(_) => "F32",
+        Literal::F64(_) => "F64",
+    }))
+}
+
+/// Returns `true` if the metavariable appears somewhere in the given value
+fn occurs(meta: &FreeVar<String>, value: &Value) -> bool {
+    match *value {
+        Value::Universe(_) | Value::Literal(_) => false,
+        Value::Pi(ref scope) | Value::Lam(ref scope) => {
+            occurs(meta, &(scope.unsafe_pattern.1).0) || occurs(meta, &scope.unsafe_body)
+        },
+        Value::RecordType(ref scope) | Value::Record(ref scope) => scope
+            .unsafe_pattern
+            .unsafe_patterns
+            .iter()
+            .any(|&(_, _, Embed(ref ann))| occurs(meta, ann)),
+        Value::Array(ref elems) => elems.iter().any(|elem| occurs(meta, elem)),
+        Value::Neutral(ref neutral, ref spine) => {
+            occurs_neutral(meta, neutral) || spine.iter().any(|arg| occurs(meta, arg))
+        },
+    }
+}
+
+/// Returns `true` if the metavariable appears somewhere in the given neutral value
+fn occurs_neutral(meta: &FreeVar<String>, neutral: &Neutral) -> bool {
+    match *neutral {
+        Neutral::Head(Head::Meta(ref other_meta)) => meta == other_meta,
+        Neutral::Head(Head::Extern(_, ref ty)) => occurs(meta, ty),
+        Neutral::Head(Head::Var(_)) | Neutral::Head(Head::Global(_)) => false,
+        Neutral::If(ref cond, ref if_true, ref if_false) => {
+            occurs_neutral(meta, cond) || occurs(meta, if_true) || occurs(meta, if_false)
+        },
+        Neutral::Proj(ref expr, _) => occurs_neutral(meta, expr),
+        Neutral::Case(ref head, ref clauses) => {
+            occurs_neutral(meta, head)
+                || clauses
+                    .iter()
+                    .any(|clause| occurs(meta, &clause.unsafe_body))
+        },
+    }
+}
+
+/// Replace the solved metavariables in an elaborated term with their
+/// solutions, returning an error if any of them remain unsolved
+pub fn zonk_term<Env>(env: &Env, term: &RcTerm) -> Result<RcTerm, TypeError>
+where
+    Env: MetaEnv,
+{
+    // Bypassing `Scope::new` and `Scope::unbind` here should be fine because
+    // solutions never contain local variables that could be captured
+    match *term.inner {
+        Term::Ann(ref expr, ref ty) => Ok(RcTerm::from(Term::Ann(
+            zonk_term(env, expr)?,
+            zonk_term(env, ty)?,
+        ))),
+        Term::Universe(_) | Term::Literal(_) | Term::Var(_) | Term::Global(_) => Ok(term.clone()),
+        Term::Meta(ref meta) => match env.get_meta_solution(meta) {
+            Some(solution) => zonk_term(env, &RcTerm::from(Term::from(&*solution))),
+            None => {
+                let (span, ty) = env
+                    .get_meta_declaration(meta)
+                    .expect("metavariable without a declaration");
+
+                Err(TypeError::UnableToElaborateHole {
+                    span,
+                    expected: Some(Box::new(ty.resugar())),
+                })
+            },
+        },
+        Term::Extern(ref name, ref ty) => Ok(RcTerm::from(Term::Extern(
+            name.clone(),
+            zonk_term(env, ty)?,
+        ))),
+        Term::Pi(ref scope) => Ok(RcTerm::from(Term::Pi(zonk_binder_scope(env, scope)?))),
+        Term::Lam(ref scope) => Ok(RcTerm::from(Term::Lam(zonk_binder_scope(env, scope)?))),
+        Term::App(ref head, ref arg) => Ok(RcTerm::from(Term::App(
+            zonk_term(env, head)?,
+            zonk_term(env, arg)?,
+        ))),
+        Term::Let(ref scope) => Ok(RcTerm::from(Term::Let(zonk_binder_scope(env, scope)?))),
+        Term::If(ref cond, ref if_true, ref if_false) => Ok(RcTerm::from(Term::If(
+            zonk_term(env, cond)?,
+            zonk_term(env, if_true)?,
+            zonk_term(env, if_false)?,
+        ))),
+        Term::RecordType(ref scope) => Ok(RcTerm::from(Term::RecordType(
+            zonk_fields_scope(env, scope)?,
+        ))),
+        Term::Record(ref scope) => Ok(RcTerm::from(Term::Record(zonk_fields_scope(env, scope)?))),
+        Term::Proj(ref expr, ref label) => Ok(RcTerm::from(Term::Proj(
+            zonk_term(env, expr)?,
+            label.clone(),
+        ))),
+        Term::Case(ref head, ref clauses) => Ok(RcTerm::from(Term::Case(
+            zonk_term(env, head)?,
+            clauses
+                .iter()
+                .map(|scope| {
+                    Ok(Scope {
+                        unsafe_pattern: zonk_pattern(env, &scope.unsafe_pattern)?,
+                        unsafe_body: zonk_term(env, &scope.unsafe_body)?,
+                    })
+                }).collect::<Result<_, TypeError>>()?,
+        ))),
+        Term::Array(ref elems) => Ok(RcTerm::from(Term::Array(
+            elems
+                .iter()
+                .map(|elem| zonk_term(env, elem))
+                .collect::<Result<_, _>>()?,
+        ))),
+    }
+}
+
+/// Replace the solved metavariables in a value with their solutions,
+/// returning an error if any of them remain unsolved
+pub fn zonk_value<Env>(env: &Env, value: &RcValue) -> Result<RcValue, TypeError>
+where
+    Env: DefinitionEnv + MetaEnv,
+{
+    let term = zonk_term(env, &RcTerm::from(Term::from(&**value)))?;
+    Ok(nf_term(env, &term)?)
+}
+
+fn zonk_pattern<Env>(env: &Env, pattern: &RcPattern) -> Result<RcPattern, TypeError>
+where
+    Env: MetaEnv,
+{
+    match *pattern.inner {
+        Pattern::Ann(ref pattern, Embed(ref ty)) => Ok(RcPattern::from(Pattern::Ann(
+            zonk_pattern(env, pattern)?,
+            Embed(zonk_term(env, ty)?),
+        ))),
+        Pattern::Binder(_) | Pattern::Literal(_) => Ok(pattern.clone()),
+    }
+}
+
+fn zonk_binder_scope<Env>(env: &Env, scope: &BinderScope) -> Result<BinderScope, TypeError>
+where
+    Env: MetaEnv,
+{
+    let (ref binder, Embed(ref ann)) = scope.unsafe_pattern;
+
+    Ok(Scope {
+        unsafe_pattern: (binder.clone(), Embed(zonk_term(env, ann)?)),
+        unsafe_body: zonk_term(env, &scope.unsafe_body)?,
+    })
+}
+
+fn zonk_fields_scope<Env>(env: &Env, scope: &FieldsScope) -> Result<FieldsScope, TypeError>
+where
+    Env: MetaEnv,
+{
+    let unsafe_patterns = scope
+        .unsafe_pattern
+        .unsafe_patterns
+        .iter()
+        .map(|&(ref label, ref binder, Embed(ref ann))| {
+            Ok((label.clone(), binder.clone(), Embed(zonk_term(env, ann)?)))
+        }).collect::<Result<_, TypeError>>()?;
+
+    Ok(Scope {
+        unsafe_pattern: Nest { unsafe_patterns },
+        unsafe_body: (),
+    })
+}
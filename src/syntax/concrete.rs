@@ -223,6 +223,12 @@ impl Pattern {
     }
 }
 
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.to_doc().group().render_fmt(pretty::FALLBACK_WIDTH, f)
+    }
+}
+
 /// Terms
 #[derive(Debug, Clone, PartialEq)]
 pub enum Term {
@@ -126,12 +126,16 @@ pub enum Term {
     Extern(String, RcTerm),
     /// A global name
     Global(String),
+    /// A metavariable, standing in for a term that is yet to be solved
+    Meta(FreeVar<String>),
     /// Dependent function types
     Pi(Scope<(Binder<String>, Embed<RcTerm>), RcTerm>),
     /// Lambda abstractions
     Lam(Scope<(Binder<String>, Embed<RcTerm>), RcTerm>),
     /// Term application
     App(RcTerm, RcTerm),
+    /// Let bindings
+    Let(Scope<(Binder<String>, Embed<RcTerm>), RcTerm>),
     /// If expression
     If(RcTerm, RcTerm, RcTerm),
     /// Dependent record types
@@ -174,7 +178,9 @@ impl RcTerm {
             Term::Ann(ref term, ref ty) => {
                 RcTerm::from(Term::Ann(term.substs(mappings), ty.substs(mappings)))
             },
-            Term::Universe(_) | Term::Literal(_) | Term::Global(_) => self.clone(),
+            Term::Universe(_) | Term::Literal(_) | Term::Global(_) | Term::Meta(_) => {
+                self.clone()
+            },
             Term::Var(ref var) => match mappings.iter().find(|&(ref name, _)| var == name) {
                 Some(&(_, ref term)) => term.clone(),
                 None => self.clone(),
@@ -199,6 +205,13 @@ impl RcTerm {
             Term::App(ref head, ref arg) => {
                 RcTerm::from(Term::App(head.substs(mappings), arg.substs(mappings)))
             },
+            Term::Let(ref scope) => {
+                let (ref name, Embed(ref term)) = scope.unsafe_pattern;
+                RcTerm::from(Term::Let(Scope {
+                    unsafe_pattern: (name.clone(), Embed(term.substs(mappings))),
+                    unsafe_body: scope.unsafe_body.substs(mappings),
+                }))
+            },
             Term::If(ref cond, ref if_true, ref if_false) => RcTerm::from(Term::If(
                 cond.substs(mappings),
                 if_true.substs(mappings),
@@ -363,7 +376,14 @@ impl Value {
     pub fn global_app(&self) -> Option<(&str, &Spine)> {
         self.head_app().and_then(|(head, spine)| match head {
             Head::Global(ref name) => Some((name.as_str(), spine)),
-            Head::Extern(_, _) | Head::Var(_) => None,
+            Head::Extern(_, _) | Head::Var(_) | Head::Meta(_) => None,
+        })
+    }
+
+    pub fn meta_app(&self) -> Option<(&FreeVar<String>, &Spine)> {
+        self.head_app().and_then(|(head, spine)| match head {
+            Head::Meta(ref meta) => Some((meta, spine)),
+            Head::Extern(_, _) | Head::Var(_) | Head::Global(_) => None,
         })
     }
 }
@@ -411,7 +431,8 @@ pub enum Head {
     Extern(String, RcType),
     /// A global name
     Global(String),
-    // TODO: Metavariables
+    /// Metavariables that have not yet been solved
+    Meta(FreeVar<String>),
 }
 
 /// The spine of a neutral term
@@ -596,6 +617,7 @@ impl<'a> From<&'a Head> for Term {
             Head::Var(ref var) => Term::Var(var.clone()),
             Head::Extern(ref name, ref ty) => Term::Extern(name.clone(), RcTerm::from(&**ty)),
             Head::Global(ref name) => Term::Global(name.clone()),
+            Head::Meta(ref meta) => Term::Meta(meta.clone()),
         }
     }
 }
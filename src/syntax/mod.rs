@@ -34,7 +34,7 @@ impl fmt::Display for Level {
 /// A label that describes the name of a field in a record
 ///
 /// Labels are significant when comparing for alpha-equality
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, BoundPattern, BoundTerm)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, BoundPattern, BoundTerm)]
 pub struct Label(pub String);
 
 impl From<String> for Label {
@@ -139,7 +139,9 @@ impl ToDoc for Term {
                         // FIXME: Indentation
                         decls.iter().map(|decl| decl.to_doc()),
                         Doc::newline(),
-                    )).append("in")
+                    )).append(Doc::space())
+                    .append("in")
+                    .append(Doc::space())
                     .append(body.to_doc())
             },
             Term::If(_, ref cond, ref if_true, ref if_false) => Doc::text("if")
@@ -160,19 +162,18 @@ impl ToDoc for Term {
                 .append("of")
                 .append(Doc::space())
                 .append("{")
-                .append(Doc::newline())
                 .append(
                     Doc::concat(clauses.iter().map(|&(ref pattern, ref body)| {
-                        pattern
-                            .to_doc()
+                        Doc::newline()
+                            .append(pattern.to_doc())
                             .append(Doc::space())
                             .append("=>")
                             .append(Doc::space())
                             .append(body.to_doc())
                             .append(";")
-                            .append(Doc::newline())
                     })).nest(INDENT_WIDTH),
-                ).append("}"),
+                ).append(Doc::newline())
+                .append("}"),
             Term::RecordType(_, ref fields) if fields.is_empty() => Doc::text("Record {}"),
             Term::Record(_, ref fields) if fields.is_empty() => Doc::text("record {}"),
             Term::RecordType(_, ref fields) => Doc::text("Record {")
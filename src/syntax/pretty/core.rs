@@ -1,6 +1,6 @@
 //! Pretty printing for the core syntax
 
-use moniker::{Binder, Embed, Var};
+use moniker::{Binder, Embed, FreeVar, Var};
 use pretty::Doc;
 use std::iter;
 
@@ -42,6 +42,10 @@ fn pretty_global(name: &str) -> StaticDoc {
     sexpr("global", Doc::as_string(name))
 }
 
+fn pretty_meta(meta: &FreeVar<String>) -> StaticDoc {
+    sexpr("meta", Doc::as_string(meta))
+}
+
 fn pretty_lam(binder: &Binder<String>, ann: &impl ToDoc, body: &impl ToDoc) -> StaticDoc {
     sexpr(
         "λ",
@@ -80,6 +84,43 @@ where
     )
 }
 
+fn pretty_let(binder: &Binder<String>, term: &impl ToDoc, body: &impl ToDoc) -> StaticDoc {
+    sexpr(
+        "let",
+        Doc::group(parens(
+            pretty_binder(binder)
+                .append(Doc::space())
+                .append(term.to_doc().group()),
+        )).append(Doc::space())
+        .append(body.to_doc()),
+    )
+}
+
+fn pretty_let_item(item: &raw::LetItem) -> StaticDoc {
+    match *item {
+        raw::LetItem::Declaration {
+            ref label,
+            term: Embed(ref ann),
+            ..
+        } => Doc::group(sexpr(
+            "declare",
+            Doc::as_string(label)
+                .append(Doc::space())
+                .append(ann.to_doc().group()),
+        )),
+        raw::LetItem::Definition {
+            ref binder,
+            term: Embed(ref term),
+            ..
+        } => Doc::group(sexpr(
+            "define",
+            pretty_binder(binder)
+                .append(Doc::space())
+                .append(term.to_doc().group()),
+        )),
+    }
+}
+
 fn pretty_if(cond: &impl ToDoc, if_true: &impl ToDoc, if_false: &impl ToDoc) -> StaticDoc {
     sexpr(
         "if",
@@ -142,6 +183,8 @@ impl ToDoc for raw::Pattern {
             raw::Pattern::Ann(ref pattern, Embed(ref ty)) => pretty_ann(&pattern.inner, &ty.inner),
             raw::Pattern::Literal(ref literal) => literal.to_doc(),
             raw::Pattern::Binder(_, ref binder) => pretty_binder(binder),
+            raw::Pattern::Global(_, ref name) => pretty_global(name),
+            raw::Pattern::Error(_) => parens(Doc::text("error")),
         }
     }
 }
@@ -167,6 +210,12 @@ impl ToDoc for raw::Term {
                 &scope.unsafe_body.inner,
             ),
             raw::Term::App(ref head, ref arg) => pretty_app(head.to_doc(), iter::once(&arg.inner)),
+            raw::Term::Let(_, ref scope) => sexpr(
+                "let",
+                Doc::concat(scope.unsafe_pattern.unsafe_patterns.iter().map(|item| {
+                    pretty_let_item(item).append(Doc::space())
+                })).append(scope.unsafe_body.to_doc()),
+            ),
             raw::Term::If(_, ref cond, ref if_true, ref if_false) => {
                 pretty_if(&cond.inner, &if_true.inner, &if_false.inner)
             },
@@ -206,6 +255,7 @@ impl ToDoc for raw::Term {
                     elems.iter().map(|elem| elem.to_doc()),
                     Doc::text(";").append(Doc::space()),
                 )).append("]"),
+            raw::Term::Error(_) => parens(Doc::text("error")),
         }
     }
 }
@@ -250,6 +300,7 @@ impl ToDoc for Term {
             Term::Var(ref var) => pretty_var(var),
             Term::Extern(ref name, ref ty) => pretty_extern(name, &ty.inner),
             Term::Global(ref name) => pretty_global(name),
+            Term::Meta(ref meta) => pretty_meta(meta),
             Term::Lam(ref scope) => pretty_lam(
                 &scope.unsafe_pattern.0,
                 &(scope.unsafe_pattern.1).0.inner,
@@ -261,6 +312,11 @@ impl ToDoc for Term {
                 &scope.unsafe_body.inner,
             ),
             Term::App(ref head, ref arg) => pretty_app(head.to_doc(), iter::once(&arg.inner)),
+            Term::Let(ref scope) => pretty_let(
+                &scope.unsafe_pattern.0,
+                &(scope.unsafe_pattern.1).0.inner,
+                &scope.unsafe_body.inner,
+            ),
             Term::If(ref cond, ref if_true, ref if_false) => {
                 pretty_if(&cond.inner, &if_true.inner, &if_false.inner)
             },
@@ -380,6 +436,7 @@ impl ToDoc for Head {
             Head::Var(ref var) => pretty_var(var),
             Head::Extern(ref name, ref ty) => pretty_extern(name, &ty.inner),
             Head::Global(ref name) => pretty_global(name),
+            Head::Meta(ref meta) => pretty_meta(meta),
         }
     }
 }
@@ -64,6 +64,34 @@ impl Item {
     }
 }
 
+/// Items within a let binding
+///
+/// Unlike top-level items, only definitions bind variables. Declarations are
+/// matched up with their definitions by label during type checking.
+#[derive(Debug, Clone, PartialEq, BoundPattern)]
+pub enum LetItem {
+    /// Declares the type associated with a label, prior to its definition
+    Declaration {
+        /// The span of source code where the label was introduced
+        label_span: ByteSpan,
+        /// The name that is being declared
+        label: Label,
+        /// The type annotation for associated with the label
+        term: Embed<RcTerm>,
+    },
+    /// Defines the term that should be associated with a label
+    Definition {
+        /// The span of source code where the label was introduced
+        label_span: ByteSpan,
+        /// The name that is being defined
+        label: Label,
+        /// The variable that this definition is bound to
+        binder: Binder<String>,
+        /// The term for associated with the label
+        term: Embed<RcTerm>,
+    },
+}
+
 /// Literals
 #[derive(Debug, Clone, PartialEq, PartialOrd, BoundTerm, BoundPattern)]
 pub enum Literal {
@@ -99,6 +127,10 @@ pub enum Pattern {
     Binder(ByteSpan, Binder<String>),
     /// Literal patterns
     Literal(Literal),
+    /// Patterns that refer to global literals, like `true` and `false`
+    Global(ByteSpan, String),
+    /// Patterns that could not be parsed
+    Error(ByteSpan),
 }
 
 impl Pattern {
@@ -106,8 +138,9 @@ impl Pattern {
     pub fn span(&self) -> ByteSpan {
         match *self {
             Pattern::Ann(ref pattern, Embed(ref ty)) => pattern.span().to(ty.span()),
-            Pattern::Binder(span, _) => span,
+            Pattern::Binder(span, _) | Pattern::Global(span, _) => span,
             Pattern::Literal(ref literal) => literal.span(),
+            Pattern::Error(span) => span,
         }
     }
 }
@@ -172,6 +205,8 @@ pub enum Term {
     Lam(ByteSpan, Scope<(Binder<String>, Embed<RcTerm>), RcTerm>),
     /// Term application
     App(RcTerm, RcTerm),
+    /// Let bindings
+    Let(ByteSpan, Scope<Nest<LetItem>, RcTerm>),
     /// If expression
     If(ByteIndex, RcTerm, RcTerm, RcTerm),
     /// Dependent record types
@@ -190,6 +225,8 @@ pub enum Term {
     Case(ByteSpan, RcTerm, Vec<Scope<RcPattern, RcTerm>>),
     /// Array literals
     Array(ByteSpan, Vec<RcTerm>),
+    /// Terms that could not be parsed
+    Error(ByteSpan),
 }
 
 impl Term {
@@ -202,11 +239,13 @@ impl Term {
             | Term::Global(span, _)
             | Term::Pi(span, _)
             | Term::Lam(span, _)
+            | Term::Let(span, _)
             | Term::RecordType(span, _)
             | Term::Record(span, _)
             | Term::Proj(span, _, _, _)
             | Term::Case(span, _, _)
-            | Term::Array(span, _) => span,
+            | Term::Array(span, _)
+            | Term::Error(span) => span,
             Term::Literal(ref literal) => literal.span(),
             Term::Ann(ref expr, ref ty) => expr.span().to(ty.span()),
             Term::App(ref head, ref arg) => head.span().to(arg.span()),
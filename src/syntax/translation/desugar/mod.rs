@@ -187,12 +187,82 @@ fn desugar_record(
     raw::RcTerm::from(raw::Term::Record(span, Scope::new(Nest::new(fields), ())))
 }
 
+/// Convert a sugary let binding from something like:
+///
+/// ```text
+/// let x : t1;
+///     x = e1;
+///     f (a : t2) = e2;
+/// in
+///     t3
+/// ```
+///
+/// To a let binding with the parameters of each definition desugared into
+/// lambdas:
+///
+/// ```text
+/// let x : t1; x = e1; f = \(a : t2) => e2; in t3
+/// ```
+///
+/// Bindings are not recursive, so each definition can only refer to the
+/// definitions that come before it. Declarations are matched up with their
+/// definitions during type checking.
+fn desugar_let(
+    env: &DesugarEnv,
+    span: ByteSpan,
+    items: &[concrete::Item],
+    body: &concrete::Term,
+) -> raw::RcTerm {
+    let mut env = env.clone();
+    let mut let_items = Vec::with_capacity(items.len());
+
+    for item in items {
+        match *item {
+            concrete::Item::Declaration {
+                name: (start, ref name),
+                ref ann,
+            } => let_items.push(raw::LetItem::Declaration {
+                label_span: ByteSpan::from_offset(start, ByteOffset::from_str(name)),
+                label: Label(name.clone()),
+                term: Embed(ann.desugar(&env)),
+            }),
+            concrete::Item::Definition {
+                name: (start, ref name),
+                ref params,
+                ref return_ann,
+                ref body,
+            } => {
+                let return_ann = return_ann.as_ref().map(<_>::as_ref);
+                let term = desugar_lam(&env, params, return_ann, body);
+
+                let_items.push(raw::LetItem::Definition {
+                    label_span: ByteSpan::from_offset(start, ByteOffset::from_str(name)),
+                    label: Label(name.clone()),
+                    binder: Binder(env.on_binding(name)),
+                    term: Embed(term),
+                });
+            },
+            // Skip erroneous items - these will have already been reported
+            // during parsing
+            concrete::Item::Error(_) => {},
+        }
+    }
+
+    raw::RcTerm::from(raw::Term::Let(
+        span,
+        Scope::new(Nest::new(let_items), body.desugar(&env)),
+    ))
+}
+
 impl Desugar<raw::Module> for concrete::Module {
     fn desugar(&self, env: &DesugarEnv) -> raw::Module {
         let mut env = env.clone();
         let concrete_items = match *self {
             concrete::Module::Valid { ref items } => items,
-            concrete::Module::Error(_) => unimplemented!("error recovery"),
+            // The parser could not recover anything from the module, so we
+            // return an empty one. The error will have already been reported
+            // during parsing
+            concrete::Module::Error(_) => return raw::Module { items: Vec::new() },
         };
 
         let mut items = Vec::with_capacity(concrete_items.len());
@@ -227,7 +297,9 @@ impl Desugar<raw::Module> for concrete::Module {
                         term,
                     }
                 },
-                concrete::Item::Error(_) => unimplemented!("error recovery"),
+                // Skip erroneous items - these will have already been reported
+                // during parsing
+                concrete::Item::Error(_) => continue,
             };
 
             items.push(item);
@@ -260,6 +332,15 @@ impl Desugar<(raw::RcPattern, DesugarEnv)> for concrete::Pattern {
 
                 (ann_pattern, env)
             },
+            // FIXME: Draw these names from some environment?
+            concrete::Pattern::Binder(_, ref name)
+                if (name == "true" || name == "false") && !env.locals.contains_key(name) =>
+            {
+                (
+                    raw::RcPattern::from(raw::Pattern::Global(span, name.clone())),
+                    env.clone(),
+                )
+            },
             concrete::Pattern::Binder(_, ref name) => {
                 let mut env = env.clone();
                 let free_var = env.on_binding(name);
@@ -271,7 +352,10 @@ impl Desugar<(raw::RcPattern, DesugarEnv)> for concrete::Pattern {
                 raw::RcPattern::from(raw::Pattern::Literal(literal.desugar(env))),
                 env.clone(),
             ),
-            concrete::Pattern::Error(_) => unimplemented!("error recovery"),
+            concrete::Pattern::Error(_) => (
+                raw::RcPattern::from(raw::Pattern::Error(span)),
+                env.clone(),
+            ),
         }
     }
 }
@@ -313,7 +397,7 @@ impl Desugar<raw::RcTerm> for concrete::Term {
                     raw::RcTerm::from(raw::Term::App(acc, arg.desugar(env)))
                 })
             },
-            concrete::Term::Let(_, ref _items, ref _body) => unimplemented!("let bindings"),
+            concrete::Term::Let(_, ref items, ref body) => desugar_let(env, span, items, body),
             concrete::Term::If(start, ref cond, ref if_true, ref if_false) => {
                 raw::RcTerm::from(raw::Term::If(
                     start,
@@ -344,7 +428,7 @@ impl Desugar<raw::RcTerm> for concrete::Term {
                     Label(label.clone()),
                 ))
             },
-            concrete::Term::Error(_) => unimplemented!("error recovery"),
+            concrete::Term::Error(span) => raw::RcTerm::from(raw::Term::Error(span)),
         }
     }
 }
@@ -51,11 +51,39 @@ mod module {
 
         concrete_module.desugar(&DesugarEnv::new());
     }
+
+    #[test]
+    fn error_item() {
+        let mut codemap = CodeMap::new();
+        let src = "foo : Type; = Type; baz = Type;";
+        let filemap = codemap.add_filemap(FileName::virtual_("test"), src.into());
+
+        let (concrete_module, errors) = parse::module(&filemap);
+        assert!(!errors.is_empty());
+
+        let raw_module = concrete_module.desugar(&DesugarEnv::new());
+        let labels = raw_module
+            .items
+            .iter()
+            .map(|item| match *item {
+                raw::Item::Declaration { ref label, .. }
+                | raw::Item::Definition { ref label, .. } => label.0.as_str(),
+            }).collect::<Vec<_>>();
+
+        assert_eq!(labels, ["foo", "baz"]);
+    }
+
+    #[test]
+    fn error_module() {
+        let concrete_module = concrete::Module::Error(ByteSpan::default());
+
+        assert!(concrete_module.desugar(&DesugarEnv::new()).items.is_empty());
+    }
 }
 
 mod term {
-    use syntax::raw::{RcTerm, Term};
-    use syntax::Level;
+    use syntax::raw::{LetItem, Literal, RcTerm, Term};
+    use syntax::{Label, Level};
 
     use super::*;
 
@@ -326,6 +354,127 @@ mod term {
         );
     }
 
+    #[test]
+    fn let_() {
+        let x = FreeVar::fresh_named("x");
+        let var_x = || RcTerm::from(Term::Var(ByteSpan::default(), Var::Free(x.clone())));
+        let one = || RcTerm::from(Term::Literal(Literal::Int(ByteSpan::default(), 1)));
+
+        assert_term_eq!(
+            parse(r"let x = 1; in x"),
+            RcTerm::from(Term::Let(
+                ByteSpan::default(),
+                Scope::new(
+                    Nest::new(vec![LetItem::Definition {
+                        label_span: ByteSpan::default(),
+                        label: Label(String::from("x")),
+                        binder: Binder(x.clone()),
+                        term: Embed(one()),
+                    }]),
+                    var_x(),
+                ),
+            )),
+        );
+    }
+
+    #[test]
+    fn let_shadow() {
+        let x1 = FreeVar::fresh_named("x");
+        let x2 = FreeVar::fresh_named("x");
+        let var_x1 = || RcTerm::from(Term::Var(ByteSpan::default(), Var::Free(x1.clone())));
+        let var_x2 = || RcTerm::from(Term::Var(ByteSpan::default(), Var::Free(x2.clone())));
+        let one = || RcTerm::from(Term::Literal(Literal::Int(ByteSpan::default(), 1)));
+
+        assert_term_eq!(
+            parse(r"let x = 1; x = x; in x"),
+            RcTerm::from(Term::Let(
+                ByteSpan::default(),
+                Scope::new(
+                    Nest::new(vec![
+                        LetItem::Definition {
+                            label_span: ByteSpan::default(),
+                            label: Label(String::from("x")),
+                            binder: Binder(x1.clone()),
+                            term: Embed(one()),
+                        },
+                        LetItem::Definition {
+                            label_span: ByteSpan::default(),
+                            label: Label(String::from("x")),
+                            binder: Binder(x2.clone()),
+                            term: Embed(var_x1()),
+                        },
+                    ]),
+                    var_x2(),
+                ),
+            )),
+        );
+    }
+
+    #[test]
+    fn let_decl() {
+        let x = FreeVar::fresh_named("x");
+        let var_x = || RcTerm::from(Term::Var(ByteSpan::default(), Var::Free(x.clone())));
+        let one = || RcTerm::from(Term::Literal(Literal::Int(ByteSpan::default(), 1)));
+        let i32_ty = || RcTerm::from(Term::Global(ByteSpan::default(), String::from("I32")));
+
+        assert_term_eq!(
+            parse(r"let x : I32; x = 1; in x"),
+            RcTerm::from(Term::Let(
+                ByteSpan::default(),
+                Scope::new(
+                    Nest::new(vec![
+                        LetItem::Declaration {
+                            label_span: ByteSpan::default(),
+                            label: Label(String::from("x")),
+                            term: Embed(i32_ty()),
+                        },
+                        LetItem::Definition {
+                            label_span: ByteSpan::default(),
+                            label: Label(String::from("x")),
+                            binder: Binder(x.clone()),
+                            term: Embed(one()),
+                        },
+                    ]),
+                    var_x(),
+                ),
+            )),
+        );
+    }
+
+    #[test]
+    fn error() {
+        let concrete_term = concrete::Term::Error(ByteSpan::default());
+
+        assert_term_eq!(
+            concrete_term.desugar(&DesugarEnv::new()),
+            RcTerm::from(Term::Error(ByteSpan::default())),
+        );
+    }
+
+    #[test]
+    fn case_error_pattern() {
+        let concrete_term = concrete::Term::Case(
+            ByteSpan::default(),
+            Box::new(concrete::Term::Hole(ByteSpan::default())),
+            vec![(
+                concrete::Pattern::Error(ByteSpan::default()),
+                concrete::Term::Hole(ByteSpan::default()),
+            )],
+        );
+
+        assert_term_eq!(
+            concrete_term.desugar(&DesugarEnv::new()),
+            RcTerm::from(Term::Case(
+                ByteSpan::default(),
+                RcTerm::from(Term::Hole(ByteSpan::default())),
+                vec![Scope::new(
+                    raw::RcPattern::from(raw::Pattern::Error(ByteSpan::default())),
+                    RcTerm::from(Term::Hole(ByteSpan::default())),
+                )],
+            )),
+        );
+    }
+
     mod sugar {
         use super::*;
 
@@ -368,5 +517,13 @@ mod term {
                 parse(r"(a : Type) -> (x : a) -> a"),
             )
         }
+
+        #[test]
+        fn let_params() {
+            assert_term_eq!(
+                parse(r"let id (a : Type) (x : a) : a = x; in id"),
+                parse(r"let id = \(a : Type) (x : a) => (x : a); in id"),
+            );
+        }
     }
 }
@@ -1,5 +1,6 @@
 use codespan::{ByteIndex, ByteSpan};
-use moniker::{Binder, BoundTerm, Embed, Scope, Var};
+use im::{HashMap, HashSet};
+use moniker::{Binder, BoundTerm, Embed, FreeVar, Scope, Var};
 
 use syntax::concrete;
 use syntax::core;
@@ -12,29 +13,30 @@ pub trait Resugar<T> {
 
 impl Resugar<concrete::Module> for core::Module {
     fn resugar(&self) -> concrete::Module {
+        let mut env = ResugarEnv::new();
         let mut items = Vec::with_capacity(self.items.len() * 2);
 
         for item in &self.items {
             match item {
                 core::Item::Declaration {
                     label,
-                    binder: _,
+                    binder,
                     ref term,
                 } => {
-                    // TODO: add label->binder mapping to locals
+                    env.on_item(label, binder);
                     items.push(concrete::Item::Declaration {
                         name: (ByteIndex::default(), label.0.clone()),
-                        ann: resugar_term(term, Prec::ANN),
+                        ann: resugar_term(&env, term, Prec::ANN),
                     });
                 },
                 core::Item::Definition {
                     label,
-                    binder: _,
+                    binder,
                     ref term,
                 } => {
-                    // TODO: add label->binder mapping to locals
+                    env.on_item(label, binder);
                     // pull lambda arguments from the body into the definition
-                    let (params, body) = match resugar_term(term, Prec::ANN) {
+                    let (params, body) = match resugar_term(&env, term, Prec::ANN) {
                         concrete::Term::Lam(_, params, body) => (params, *body),
                         body => (vec![], body),
                     };
@@ -83,40 +85,107 @@ fn parens_if(should_wrap: bool, inner: concrete::Term) -> concrete::Term {
     }
 }
 
-// TODO: Use this for name-avoidance
-// const USED_NAMES: &[&str] = &[
-//     // Keywords
-//     "as",
-//     "else",
-//     "_",
-//     "if",
-//     "import",
-//     "then",
-//     "Type",
-//     // Primitives
-//     "true",
-//     "false",
-//     "Bool",
-//     "String",
-//     "Char",
-//     "U8",
-//     "U16",
-//     "U32",
-//     "U64",
-//     "I8",
-//     "I16",
-//     "I32",
-//     "I64",
-//     "F32",
-//     "F64",
-// ];
-
-fn resugar_pattern(pattern: &core::Pattern, _prec: Prec) -> concrete::Pattern {
+/// Names that should never be chosen for local binders
+const USED_NAMES: &[&str] = &[
+    // Keywords
+    "as",
+    "case",
+    "else",
+    "extern",
+    "_",
+    "if",
+    "import",
+    "in",
+    "let",
+    "of",
+    "record",
+    "Record",
+    "then",
+    "Type",
+    // Primitives
+    "true",
+    "false",
+    "Bool",
+    "String",
+    "Char",
+    "U8",
+    "U16",
+    "U32",
+    "U64",
+    "I8",
+    "I16",
+    "I32",
+    "I64",
+    "F32",
+    "F64",
+];
+
+/// The environment used when resugaring from the core to the concrete syntax
+#[derive(Debug, Clone)]
+struct ResugarEnv {
+    /// The names that have been assigned to the free variables in scope
+    names: HashMap<FreeVar<String>, String>,
+    /// The names that are currently in use, and would be shadowed if they
+    /// were assigned to another binder
+    usages: HashSet<String>,
+}
+
+impl ResugarEnv {
+    fn new() -> ResugarEnv {
+        ResugarEnv {
+            names: HashMap::new(),
+            usages: USED_NAMES.iter().map(|&name| String::from(name)).collect(),
+        }
+    }
+
+    /// Refer to the binder of an item (or record field) using its label
+    fn on_item(&mut self, label: &Label, binder: &Binder<String>) -> String {
+        let Label(ref name) = *label;
+        self.names.insert(binder.0.clone(), name.clone());
+        self.usages.insert(name.clone());
+        name.clone()
+    }
+
+    /// Choose a name for a binder, preferring its pretty name if it would
+    /// not shadow another name that is currently in use
+    fn on_binder(&mut self, binder: &Binder<String>) -> String {
+        let base_name = binder.0.pretty_name.as_ref().map_or("a", String::as_str);
+        let name = if self.usages.contains(base_name) {
+            (1..)
+                .map(|count: u32| format!("{}{}", base_name, count))
+                .find(|name| !self.usages.contains(name))
+                .unwrap()
+        } else {
+            String::from(base_name)
+        };
+
+        self.names.insert(binder.0.clone(), name.clone());
+        self.usages.insert(name.clone());
+        name
+    }
+
+    fn on_free_var(&self, free_var: &FreeVar<String>) -> String {
+        match self.names.get(free_var) {
+            Some(name) => name.clone(),
+            // This variable was bound outside of the term being resugared
+            None => free_var.to_string(),
+        }
+    }
+}
+
+fn resugar_pattern(
+    env: &mut ResugarEnv,
+    pattern: &core::Pattern,
+    _prec: Prec,
+) -> concrete::Pattern {
     match *pattern {
-        core::Pattern::Ann(ref pattern, Embed(ref ty)) => concrete::Pattern::Ann(
-            Box::new(resugar_pattern(pattern, Prec::NO_WRAP)),
-            Box::new(resugar_term(ty, Prec::LAM)),
-        ),
+        core::Pattern::Ann(ref pattern, Embed(ref ty)) => {
+            let ty = resugar_term(env, ty, Prec::LAM);
+            concrete::Pattern::Ann(
+                Box::new(resugar_pattern(env, pattern, Prec::NO_WRAP)),
+                Box::new(ty),
+            )
+        },
         // core::Pattern::Literal(ref literal) => concrete::Pattern::Literal(resugar_literal(lit)),
         core::Pattern::Literal(ref literal) => {
             use syntax::concrete::{Literal, Pattern};
@@ -150,19 +219,19 @@ fn resugar_pattern(pattern: &core::Pattern, _prec: Prec) -> concrete::Pattern {
                 core::Literal::F64(value) => Pattern::Literal(Literal::Float(span, value)),
             }
         },
-        core::Pattern::Binder(Binder(ref free_var)) => {
-            // TODO: use pretty_name if it is present, and not used in the current scope
-            // TODO: otherwise create a pretty name
-            concrete::Pattern::Binder(ByteIndex::default(), free_var.to_string())
+        core::Pattern::Binder(ref binder) => {
+            concrete::Pattern::Binder(ByteIndex::default(), env.on_binder(binder))
         },
     }
 }
 
 fn resugar_pi(
+    env: &ResugarEnv,
     scope: &Scope<(Binder<String>, Embed<core::RcTerm>), core::RcTerm>,
     prec: Prec,
 ) -> concrete::Term {
     let ((Binder(fv), Embed(mut ann)), mut body) = scope.clone().unbind();
+    let mut env = env.clone();
 
     // Only use explicit parameter names if the body is dependent on
     // the parameter or there is a human-readable name given.
@@ -170,14 +239,9 @@ fn resugar_pi(
     // We'll be checking for readable names as we go, because if they've
     // survived until now they're probably desirable to retain!
     if body.free_vars().contains(&fv) || fv.pretty_name.is_some() {
-        // TODO: use name if it is present, and not used in the current scope
-        // TODO: otherwise create a pretty name
-        // TODO: add the used name to the environment
-
-        let mut params = vec![(
-            vec![(ByteIndex::default(), fv.to_string())],
-            resugar_term(&ann, Prec::APP),
-        )];
+        let ann_term = resugar_term(&env, &ann, Prec::PI);
+        let name = env.on_binder(&Binder(fv));
+        let mut params = vec![(vec![(ByteIndex::default(), name)], ann_term)];
 
         // Argument resugaring
         #[cfg_attr(feature = "cargo-clippy", allow(while_let_loop))] // Need NLL in stable!
@@ -202,15 +266,14 @@ fn resugar_pi(
                 // (a : Type) (b : Type) -> ...
                 // (a b : Type) -> ...
                 // ```
-                let next_param = (ByteIndex::default(), next_fv.to_string());
+                let next_param = (ByteIndex::default(), env.on_binder(&Binder(next_fv)));
                 params.last_mut().unwrap().0.push(next_param);
             } else if next_body.free_vars().contains(&next_fv) || next_fv.pretty_name.is_some() {
                 // Add a new parameter if the body is dependent on the parameter
                 // or there is a human-readable name given
-                params.push((
-                    vec![(ByteIndex::default(), next_fv.to_string())],
-                    resugar_term(&next_ann, Prec::APP),
-                ));
+                let next_ann_term = resugar_term(&env, &next_ann, Prec::PI);
+                let next_name = env.on_binder(&Binder(next_fv));
+                params.push((vec![(ByteIndex::default(), next_name)], next_ann_term));
             } else {
                 // Stop collapsing parameters if we encounter a non-dependent pi type.
                 return parens_if(
@@ -219,8 +282,8 @@ fn resugar_pi(
                         ByteIndex::default(),
                         params,
                         Box::new(concrete::Term::Arrow(
-                            Box::new(resugar_term(&next_ann, Prec::APP)),
-                            Box::new(resugar_term(&next_body, Prec::LAM)),
+                            Box::new(resugar_term(&env, &next_ann, Prec::APP)),
+                            Box::new(resugar_term(&env, &next_body, Prec::LAM)),
                         )),
                     ),
                 );
@@ -235,7 +298,7 @@ fn resugar_pi(
             concrete::Term::Pi(
                 ByteIndex::default(),
                 params,
-                Box::new(resugar_term(&body, Prec::LAM)),
+                Box::new(resugar_term(&env, &body, Prec::LAM)),
             ),
         )
     } else {
@@ -249,26 +312,24 @@ fn resugar_pi(
         parens_if(
             Prec::PI < prec,
             concrete::Term::Arrow(
-                Box::new(resugar_term(&ann, Prec::APP)),
-                Box::new(resugar_term(&body, Prec::LAM)),
+                Box::new(resugar_term(&env, &ann, Prec::APP)),
+                Box::new(resugar_term(&env, &body, Prec::LAM)),
             ),
         )
     }
 }
 
 fn resugar_lam(
+    env: &ResugarEnv,
     scope: &Scope<(Binder<String>, Embed<core::RcTerm>), core::RcTerm>,
     prec: Prec,
 ) -> concrete::Term {
-    let ((name, Embed(mut ann)), mut body) = scope.clone().unbind();
+    let ((binder, Embed(mut ann)), mut body) = scope.clone().unbind();
+    let mut env = env.clone();
 
-    // TODO: use name if it is present, and not used in the current scope
-    // TODO: otherwise create a pretty name
-    // TODO: add the used name to the environment
-    let mut params = vec![(
-        vec![(ByteIndex::default(), name.to_string())],
-        Some(Box::new(resugar_term(&ann, Prec::LAM))),
-    )];
+    let ann_term = resugar_term(&env, &ann, Prec::PI);
+    let name = env.on_binder(&binder);
+    let mut params = vec![(vec![(ByteIndex::default(), name)], Some(Box::new(ann_term)))];
 
     // Argument resugaring
     #[cfg_attr(feature = "cargo-clippy", allow(while_let_loop))] // Need NLL in stable!
@@ -293,12 +354,14 @@ fn resugar_lam(
         // \(a b : Type) => ...
         // ```
         if core::Term::term_eq(&ann, &next_ann) {
-            let next_param = (ByteIndex::default(), next_fv.to_string());
+            let next_param = (ByteIndex::default(), env.on_binder(&Binder(next_fv)));
             params.last_mut().unwrap().0.push(next_param);
         } else {
+            let next_ann_term = resugar_term(&env, &next_ann, Prec::PI);
+            let next_name = env.on_binder(&Binder(next_fv));
             params.push((
-                vec![(ByteIndex::default(), next_fv.to_string())],
-                Some(Box::new(resugar_term(&next_ann, Prec::LAM))),
+                vec![(ByteIndex::default(), next_name)],
+                Some(Box::new(next_ann_term)),
             ));
         }
 
@@ -311,18 +374,90 @@ fn resugar_lam(
         concrete::Term::Lam(
             ByteIndex::default(),
             params,
-            Box::new(resugar_term(&body, Prec::LAM)),
+            Box::new(resugar_term(&env, &body, Prec::LAM)),
+        ),
+    )
+}
+
+fn resugar_let(
+    env: &ResugarEnv,
+    scope: &Scope<(Binder<String>, Embed<core::RcTerm>), core::RcTerm>,
+    prec: Prec,
+) -> concrete::Term {
+    let ((binder, Embed(term)), mut body) = scope.clone().unbind();
+    let mut env = env.clone();
+    let mut items = Vec::new();
+    push_let_items(&mut env, &mut items, &binder, &term);
+
+    // Share a single `let` if another binding is nested directly inside.
+    // For example:
+    //
+    // ```
+    // let x = 1; in let y = 2; in ...
+    // let x = 1; y = 2; in ...
+    // ```
+    while let core::Term::Let(ref scope) = *body {
+        let ((next_binder, Embed(next_term)), next_body) = scope.clone().unbind();
+        push_let_items(&mut env, &mut items, &next_binder, &next_term);
+        body = next_body;
+    }
+
+    parens_if(
+        Prec::LAM < prec,
+        concrete::Term::Let(
+            ByteIndex::default(),
+            items,
+            Box::new(resugar_term(&env, &body, Prec::LAM)),
         ),
     )
 }
 
-fn resugar_term(term: &core::Term, prec: Prec) -> concrete::Term {
+/// Add the items for a let binding, splitting annotated terms into a
+/// declaration followed by a definition
+fn push_let_items(
+    env: &mut ResugarEnv,
+    items: &mut Vec<concrete::Item>,
+    binder: &Binder<String>,
+    term: &core::Term,
+) {
+    // The bound term can't refer to the binder, so resugar it first
+    let (ann, body) = match *term {
+        core::Term::Ann(ref term, ref ty) => (
+            Some(resugar_term(env, ty, Prec::ANN)),
+            resugar_term(env, term, Prec::ANN),
+        ),
+        _ => (None, resugar_term(env, term, Prec::ANN)),
+    };
+    let name = env.on_binder(binder);
+
+    if let Some(ann) = ann {
+        items.push(concrete::Item::Declaration {
+            name: (ByteIndex::default(), name.clone()),
+            ann,
+        });
+    }
+
+    // pull lambda arguments from the body into the definition
+    let (params, body) = match body {
+        concrete::Term::Lam(_, params, body) => (params, *body),
+        body => (vec![], body),
+    };
+
+    items.push(concrete::Item::Definition {
+        name: (ByteIndex::default(), name),
+        return_ann: None,
+        params,
+        body,
+    });
+}
+
+fn resugar_term(env: &ResugarEnv, term: &core::Term, prec: Prec) -> concrete::Term {
     match *term {
         core::Term::Ann(ref term, ref ty) => parens_if(
             Prec::ANN < prec,
             concrete::Term::Ann(
-                Box::new(resugar_term(term, Prec::LAM)),
-                Box::new(resugar_term(ty, Prec::ANN)),
+                Box::new(resugar_term(env, term, Prec::LAM)),
+                Box::new(resugar_term(env, ty, Prec::ANN)),
             ),
         ),
         core::Term::Universe(level) => {
@@ -367,11 +502,11 @@ fn resugar_term(term: &core::Term, prec: Prec) -> concrete::Term {
             }
         },
         core::Term::Var(Var::Free(ref free_var)) => {
-            // TODO: use name if it is present, and not used in the current scope
-            // TODO: otherwise create a pretty name
-            concrete::Term::Name(ByteIndex::default(), free_var.to_string())
+            concrete::Term::Name(ByteIndex::default(), env.on_free_var(free_var))
         },
         core::Term::Global(ref name) => concrete::Term::Name(ByteIndex::default(), name.clone()),
+        // Metavariables are displayed as holes, as they are yet to be solved
+        core::Term::Meta(_) => concrete::Term::Hole(ByteSpan::default()),
         core::Term::Var(Var::Bound(_)) => {
             // TODO: Better message
             panic!("Tried to convert a term that was not locally closed");
@@ -380,81 +515,101 @@ fn resugar_term(term: &core::Term, prec: Prec) -> concrete::Term {
             ByteSpan::default(),
             ByteSpan::default(),
             name.clone(),
-            Box::new(resugar_term(ty, Prec::NO_WRAP)),
-        ),
-        core::Term::Pi(ref scope) => resugar_pi(scope, prec),
-        core::Term::Lam(ref scope) => resugar_lam(scope, prec),
-        core::Term::App(ref head, ref arg) => parens_if(
-            Prec::APP < prec,
-            concrete::Term::App(
-                Box::new(resugar_term(head, Prec::NO_WRAP)),
-                vec![resugar_term(arg, Prec::NO_WRAP)], // TODO
-            ),
+            Box::new(resugar_term(env, ty, Prec::NO_WRAP)),
         ),
+        core::Term::Pi(ref scope) => resugar_pi(env, scope, prec),
+        core::Term::Lam(ref scope) => resugar_lam(env, scope, prec),
+        core::Term::App(ref head, ref arg) => {
+            // Collect the arguments of nested applications into a single
+            // application. For example:
+            //
+            // ```
+            // ((f a) b) c
+            // f a b c
+            // ```
+            let mut head = head;
+            let mut args = vec![resugar_term(env, arg, Prec::ATOMIC)];
+            while let core::Term::App(ref next_head, ref next_arg) = *head.inner {
+                args.push(resugar_term(env, next_arg, Prec::ATOMIC));
+                head = next_head;
+            }
+            args.reverse();
+
+            parens_if(
+                Prec::APP < prec,
+                concrete::Term::App(Box::new(resugar_term(env, head, Prec::ATOMIC)), args),
+            )
+        },
+        core::Term::Let(ref scope) => resugar_let(env, scope, prec),
         core::Term::If(ref cond, ref if_true, ref if_false) => parens_if(
             Prec::LAM < prec,
             concrete::Term::If(
                 ByteIndex::default(),
-                Box::new(resugar_term(cond, Prec::APP)),
-                Box::new(resugar_term(if_true, Prec::APP)),
-                Box::new(resugar_term(if_false, Prec::APP)),
+                Box::new(resugar_term(env, cond, Prec::APP)),
+                Box::new(resugar_term(env, if_true, Prec::APP)),
+                Box::new(resugar_term(env, if_false, Prec::APP)),
             ),
         ),
         core::Term::RecordType(ref scope) => {
             let (scope, ()) = scope.clone().unbind();
+            let mut env = env.clone();
 
             let fields = scope
                 .unnest()
                 .into_iter()
-                .map(|(Label(label), _, Embed(term))| {
-                    // TODO: add label->binder mapping to locals
-                    let term = resugar_term(&term, Prec::NO_WRAP);
-                    (ByteIndex::default(), label, term)
+                .map(|(label, binder, Embed(term))| {
+                    let term = resugar_term(&env, &term, Prec::NO_WRAP);
+                    (ByteIndex::default(), env.on_item(&label, &binder), term)
                 }).collect();
 
             concrete::Term::RecordType(ByteSpan::default(), fields)
         },
         core::Term::Record(ref scope) => {
             let (scope, ()) = scope.clone().unbind();
+            let mut env = env.clone();
 
             let fields = scope
                 .unnest()
                 .into_iter()
-                .map(|(Label(label), _, Embed(term))| {
-                    // TODO: add label->binder mapping to locals
-                    let (term_params, term_body) = match resugar_term(&term, Prec::NO_WRAP) {
+                .map(|(label, binder, Embed(term))| {
+                    let (term_params, term_body) = match resugar_term(&env, &term, Prec::NO_WRAP) {
                         concrete::Term::Lam(_, params, term_body) => (params, *term_body),
                         term_body => (vec![], term_body),
                     };
+                    let name = env.on_item(&label, &binder);
 
-                    (ByteIndex::default(), label, term_params, None, term_body)
+                    (ByteIndex::default(), name, term_params, None, term_body)
                 }).collect();
 
             concrete::Term::Record(ByteSpan::default(), fields)
         },
         core::Term::Proj(ref expr, Label(ref label)) => concrete::Term::Proj(
-            Box::new(resugar_term(expr, Prec::ATOMIC)),
+            Box::new(resugar_term(env, expr, Prec::ATOMIC)),
             ByteIndex::default(),
             label.clone(),
         ),
-        core::Term::Case(ref head, ref clauses) => concrete::Term::Case(
-            ByteSpan::default(),
-            Box::new(resugar_term(head, Prec::NO_WRAP)),
-            clauses
-                .iter()
-                .map(|scope| {
-                    let (pattern, term) = scope.clone().unbind();
-                    (
-                        resugar_pattern(&pattern, Prec::NO_WRAP),
-                        resugar_term(&term, Prec::NO_WRAP),
-                    )
-                }).collect(),
+        core::Term::Case(ref head, ref clauses) => parens_if(
+            Prec::LAM < prec,
+            concrete::Term::Case(
+                ByteSpan::default(),
+                Box::new(resugar_term(env, head, Prec::APP)),
+                clauses
+                    .iter()
+                    .map(|scope| {
+                        let (pattern, term) = scope.clone().unbind();
+                        let mut env = env.clone();
+                        (
+                            resugar_pattern(&mut env, &pattern, Prec::NO_WRAP),
+                            resugar_term(&env, &term, Prec::NO_WRAP),
+                        )
+                    }).collect(),
+            ),
         ),
         core::Term::Array(ref elems) => concrete::Term::Array(
             ByteSpan::default(),
             elems
                 .iter()
-                .map(|elem| resugar_term(elem, Prec::NO_WRAP))
+                .map(|elem| resugar_term(env, elem, Prec::NO_WRAP))
                 .collect(),
         ),
     }
@@ -462,20 +617,20 @@ fn resugar_term(term: &core::Term, prec: Prec) -> concrete::Term {
 
 impl Resugar<concrete::Term> for core::Term {
     fn resugar(&self) -> concrete::Term {
-        resugar_term(self, Prec::NO_WRAP)
+        resugar_term(&ResugarEnv::new(), self, Prec::NO_WRAP)
     }
 }
 
 impl Resugar<concrete::Term> for core::Value {
     fn resugar(&self) -> concrete::Term {
         // FIXME: Make this more efficient?
-        resugar_term(&core::Term::from(self), Prec::NO_WRAP)
+        resugar_term(&ResugarEnv::new(), &core::Term::from(self), Prec::NO_WRAP)
     }
 }
 
 impl Resugar<concrete::Term> for core::Neutral {
     fn resugar(&self) -> concrete::Term {
         // FIXME: Make this more efficient?
-        resugar_term(&core::Term::from(self), Prec::NO_WRAP)
+        resugar_term(&ResugarEnv::new(), &core::Term::from(self), Prec::NO_WRAP)
     }
 }